@@ -16,4 +16,78 @@ pub enum PlatformError {
     OutOfMemory,
     #[msg("Deserialization Error")]
     DeserializationError,
+    #[msg("Missing ramp results")]
+    MissingRampResults,
+    #[msg("Missing ramp account")]
+    MissingRampAccount,
+    #[msg("Invalid ramp account")]
+    InvalidRampAccount,
+    #[msg("Ramp txs do not match the platform")]
+    InvalidRampTxsHash,
+    #[msg("Insufficient pending withdraw")]
+    InsufficientPendingWithdraw,
+    #[msg("Invalid withdrawal receipt")]
+    InvalidWithdrawalReceipt,
+    #[msg("Invalid receipt proof")]
+    InvalidReceiptProof,
+    #[msg("Invalid state proof")]
+    InvalidStateProof,
+    #[msg("Invalid block header")]
+    InvalidBlockHeader,
+    #[msg("Ramp results do not match the proof")]
+    InvalidRampResults,
+    #[msg("The batch does not follow the last proven batch")]
+    InvalidBatchNumber,
+    #[msg("Signer is not the sequencer")]
+    InvalidSequencer,
+    #[msg("Insufficient sequencer bond")]
+    InsufficientBond,
+    #[msg("Sequencer rotation is not ready")]
+    RotationNotReady,
+    #[msg("The sequencer can't report itself")]
+    SelfReport,
+    #[msg("Ramp or forced txs are not overdue")]
+    BatchNotOverdue,
+    #[msg("Ramp txs are overdue, the sequencer must be slashed first")]
+    BatchOverdue,
+    #[msg("Invalid forced transaction")]
+    InvalidForcedTx,
+    #[msg("Forced txs do not match the platform")]
+    InvalidForcedTxsHash,
+    #[msg("Forced txs must be executed from the first one waiting for a proof")]
+    InvalidForcedTxsRange,
+    #[msg("Missing forced tx account")]
+    MissingForcedTx,
+    #[msg("Invalid forced tx account")]
+    InvalidForcedTxAccount,
+    #[msg("A forced tx queued past the inclusion delay was not executed")]
+    ForcedTxNotExecuted,
+    #[msg("The platform is frozen")]
+    PlatformFrozen,
+    #[msg("The platform is not frozen")]
+    PlatformNotFrozen,
+    #[msg("The escape hatch is not open yet")]
+    EscapeHatchClosed,
+    #[msg("The escape hatch delay is below the minimum")]
+    EscapeHatchTooShort,
+    #[msg("Invalid escaped account")]
+    InvalidEscapeAccount,
+    #[msg("Too many ramp txs are waiting for a proof")]
+    RampQueueFull,
+    #[msg("Ramp amount is below the minimum")]
+    RampAmountTooSmall,
+    #[msg("Missing ramp queue page")]
+    MissingRampQueuePage,
+    #[msg("Invalid ramp queue page")]
+    InvalidRampQueuePage,
+    #[msg("Ramp txs proven past the end of the queue")]
+    InvalidRampQueueRange,
+    #[msg("Proof write is out of bounds or not aligned to chunks")]
+    InvalidProofWrite,
+    #[msg("Proof chunk was already written")]
+    ProofWriteOverlap,
+    #[msg("Proof upload is incomplete")]
+    IncompleteProof,
+    #[msg("Another proof is being uploaded")]
+    ProofMismatch,
 }
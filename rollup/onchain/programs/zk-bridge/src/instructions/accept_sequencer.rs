@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+
+use crate::errors::PlatformError;
+use crate::state::platform::Platform;
+use crate::state::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct AcceptSequencerArgs {
+    /// Lamports bonded by the new sequencer, at least [MIN_SEQUENCER_BOND]
+    pub bond: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(args: AcceptSequencerArgs)]
+pub struct AcceptSequencer<'info> {
+    #[account(mut)]
+    pub new_sequencer: Signer<'info>,
+    /// CHECK: Only receives the bond and fees of the previous sequencer
+    #[account(mut, address = platform.sequencer)]
+    pub previous_sequencer: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [
+            PLATFORM_SEED_PREFIX,
+            platform.id.as_ref(),
+        ],
+        bump,
+        constraint = platform.pending_sequencer == Some(new_sequencer.key()) @ PlatformError::InvalidSequencer
+    )]
+    pub platform: Account<'info, Platform>,
+    pub system_program: Program<'info, System>,
+}
+
+impl AcceptSequencer<'_> {
+    /// Takes over as the sequencer once the rotation delay has passed, bonding
+    /// lamports and returning the bond and unclaimed fees of the previous
+    /// sequencer
+    pub fn handle(ctx: Context<Self>, args: AcceptSequencerArgs) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        if now < ctx.accounts.platform.rotation_timestamp {
+            return Err(PlatformError::RotationNotReady.into());
+        }
+        // The previous sequencer is accountable for the batches it withheld
+        if ctx.accounts.platform.is_batch_overdue(now)
+            && ctx.accounts.platform.bond >= MIN_SEQUENCER_BOND
+        {
+            return Err(PlatformError::BatchOverdue.into());
+        }
+        if args.bond < MIN_SEQUENCER_BOND {
+            return Err(PlatformError::InsufficientBond.into());
+        }
+
+        transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.new_sequencer.to_account_info(),
+                    to: ctx.accounts.platform.to_account_info(),
+                },
+            ),
+            args.bond,
+        )?;
+
+        let previous = ctx.accounts.platform.bond + ctx.accounts.platform.fees;
+        ctx.accounts.platform.sub_lamports(previous)?;
+        ctx.accounts.previous_sequencer.add_lamports(previous)?;
+
+        let platform = &mut ctx.accounts.platform;
+        platform.sequencer = ctx.accounts.new_sequencer.key();
+        platform.bond = args.bond;
+        platform.fees = 0;
+        platform.pending_sequencer = None;
+        platform.rotation_timestamp = 0;
+        // The new sequencer has until the next deadline to prove the queued txs
+        if platform.pending_since != 0 {
+            platform.pending_since = now;
+        }
+
+        Ok(())
+    }
+}
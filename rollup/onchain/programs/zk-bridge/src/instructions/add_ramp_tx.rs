@@ -1,7 +1,10 @@
 use crate::errors::PlatformError;
 use crate::state::platform::Platform;
 use crate::state::ramp::Ramp;
-use crate::state::{PLATFORM_SEED_PREFIX, RAMP_SEED_PREFIX};
+use crate::state::{
+    RampQueuePage, MIN_RAMP_AMOUNT, PLATFORM_SEED_PREFIX, RAMP_QUEUE_SEED_PREFIX, RAMP_SEED_PREFIX,
+    RAMP_TX_FEE,
+};
 use crate::utils::RampTx;
 use anchor_lang::prelude::*;
 use anchor_lang::system_program::{transfer, Transfer};
@@ -19,16 +22,27 @@ pub struct AddRampTx<'info> {
     pub ramper: Signer<'info>,
     #[account(
         mut,
-        realloc = 8 + std::mem::size_of_val(&platform) + std::mem::size_of::<RampTx>(),
-        realloc::payer = ramper,
-        realloc::zero = false,
         seeds = [
             PLATFORM_SEED_PREFIX,
             platform.id.as_ref(),
         ],
-        bump
+        bump,
+        constraint = !platform.frozen @ PlatformError::PlatformFrozen
     )]
     pub platform: Account<'info, Platform>,
+    /// Page of the queue the ramp tx is pushed to
+    #[account(
+        init_if_needed,
+        payer = ramper,
+        space = 8 + RampQueuePage::INIT_SPACE,
+        seeds = [
+            RAMP_QUEUE_SEED_PREFIX,
+            platform.id.as_ref(),
+            &platform.ramp_queue_page().to_le_bytes(),
+        ],
+        bump
+    )]
+    pub ramp_queue_page: Account<'info, RampQueuePage>,
     #[account(
         init_if_needed,
         payer = ramper,
@@ -46,6 +60,10 @@ pub struct AddRampTx<'info> {
 
 impl AddRampTx<'_> {
     pub fn handle(ctx: Context<Self>, args: AddRampTxArgs) -> Result<()> {
+        if args.amount < MIN_RAMP_AMOUNT {
+            return Err(PlatformError::RampAmountTooSmall.into());
+        }
+
         if ctx.accounts.ramp.ramper.eq(&Pubkey::default()) {
             ctx.accounts.ramp.set_inner(Ramp {
                 bump: ctx.bumps.ramp,
@@ -54,6 +72,21 @@ impl AddRampTx<'_> {
                 pending_withdraw: 0,
             });
         }
+        ctx.accounts.ramp_queue_page.bump = ctx.bumps.ramp_queue_page;
+        ctx.accounts.ramp_queue_page.index = ctx.accounts.platform.ramp_queue_page();
+
+        // The fee goes to the sequencer, so that flooding the queue has a cost
+        transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.ramper.to_account_info(),
+                    to: ctx.accounts.platform.to_account_info(),
+                },
+            ),
+            RAMP_TX_FEE,
+        )?;
+        ctx.accounts.platform.fees += RAMP_TX_FEE;
 
         if args.is_onramp {
             ctx.accounts.platform.deposit += args.amount;
@@ -70,21 +103,22 @@ impl AddRampTx<'_> {
             // ctx.accounts.ramper.sub_lamports(args.amount)?;
             // ctx.accounts.platform.add_lamports(args.amount)?;
         } else {
-            ctx.accounts.platform.withdraw += args.amount;
-            if ctx.accounts.platform.withdraw > ctx.accounts.platform.deposit {
+            // The withdrawal is only credited once the proof shows it was applied on L2
+            if ctx.accounts.platform.withdraw + args.amount > ctx.accounts.platform.deposit {
                 return Err(PlatformError::InsufficientDeposits.into());
             }
 
             ctx.accounts.ramp.current_state_hash = ctx.accounts.platform.last_state_hash;
-            ctx.accounts.ramp.pending_withdraw += args.amount;
         }
 
-        ctx.accounts.platform.ramp_txs.push(RampTx {
-            is_onramp: args.is_onramp,
-            amount: args.amount,
-            user: ctx.accounts.ramper.key(),
-        });
-
-        Ok(())
+        ctx.accounts.platform.push_ramp_tx(
+            &mut ctx.accounts.ramp_queue_page,
+            RampTx {
+                is_onramp: args.is_onramp,
+                amount: args.amount,
+                user: ctx.accounts.ramper.key(),
+                token: None,
+            },
+        )
     }
 }
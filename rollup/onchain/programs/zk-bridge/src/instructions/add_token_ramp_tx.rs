@@ -0,0 +1,182 @@
+use crate::errors::PlatformError;
+use crate::state::platform::Platform;
+use crate::state::ramp::Ramp;
+use crate::state::{
+    min_token_ramp_amount, RampQueuePage, PLATFORM_SEED_PREFIX, RAMP_QUEUE_SEED_PREFIX,
+    RAMP_SEED_PREFIX, RAMP_TX_FEE, VAULT_SEED_PREFIX,
+};
+use crate::utils::{RampTx, TokenAmount};
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct AddTokenRampTxArgs {
+    pub is_onramp: bool,
+    /// Tokens moved in or out of the rollup
+    pub amount: u64,
+    /// Lamports moved along with the tokens. On-ramps pay the rent of the
+    /// mirrored mint and token account out of them when these don't exist on
+    /// L2 yet, or else the tokens are refunded.
+    pub lamports: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(args: AddTokenRampTxArgs)]
+pub struct AddTokenRampTx<'info> {
+    #[account(mut)]
+    pub ramper: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            PLATFORM_SEED_PREFIX,
+            platform.id.as_ref(),
+        ],
+        bump,
+        constraint = !platform.frozen @ PlatformError::PlatformFrozen
+    )]
+    pub platform: Account<'info, Platform>,
+    /// Page of the queue the ramp tx is pushed to
+    #[account(
+        init_if_needed,
+        payer = ramper,
+        space = 8 + RampQueuePage::INIT_SPACE,
+        seeds = [
+            RAMP_QUEUE_SEED_PREFIX,
+            platform.id.as_ref(),
+            &platform.ramp_queue_page().to_le_bytes(),
+        ],
+        bump
+    )]
+    pub ramp_queue_page: Account<'info, RampQueuePage>,
+    #[account(
+        init_if_needed,
+        payer = ramper,
+        space = 8 + std::mem::size_of::<Ramp>(),
+        seeds = [
+            RAMP_SEED_PREFIX,
+            platform.id.as_ref(),
+            ramper.key().as_ref(),
+        ],
+        bump
+    )]
+    pub ramp: Account<'info, Ramp>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = ramper,
+        token::token_program = token_program,
+    )]
+    pub ramper_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// Locks the tokens of the mint while they are in the rollup
+    #[account(
+        init_if_needed,
+        payer = ramper,
+        token::mint = mint,
+        token::authority = platform,
+        token::token_program = token_program,
+        seeds = [
+            VAULT_SEED_PREFIX,
+            platform.id.as_ref(),
+            mint.key().as_ref(),
+        ],
+        bump
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl AddTokenRampTx<'_> {
+    pub fn handle(ctx: Context<Self>, args: AddTokenRampTxArgs) -> Result<()> {
+        if ctx.accounts.ramp.ramper.eq(&Pubkey::default()) {
+            ctx.accounts.ramp.set_inner(Ramp {
+                bump: ctx.bumps.ramp,
+                ramper: ctx.accounts.ramper.key(),
+                current_state_hash: ctx.accounts.platform.last_state_hash,
+                pending_withdraw: 0,
+            });
+        }
+        ctx.accounts.ramp_queue_page.bump = ctx.bumps.ramp_queue_page;
+        ctx.accounts.ramp_queue_page.index = ctx.accounts.platform.ramp_queue_page();
+
+        // The fee goes to the sequencer, so that flooding the queue has a cost
+        transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.ramper.to_account_info(),
+                    to: ctx.accounts.platform.to_account_info(),
+                },
+            ),
+            RAMP_TX_FEE,
+        )?;
+        ctx.accounts.platform.fees += RAMP_TX_FEE;
+
+        let amount = if args.is_onramp {
+            ctx.accounts.platform.deposit += args.lamports;
+            transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.ramper.to_account_info(),
+                        to: ctx.accounts.platform.to_account_info(),
+                    },
+                ),
+                args.lamports,
+            )?;
+
+            // Only the tokens received by the vault are minted on L2, which is
+            // less than the amount sent for mints charging transfer fees
+            let vault_amount = ctx.accounts.vault.amount;
+            transfer_checked(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.ramper_token_account.to_account_info(),
+                        mint: ctx.accounts.mint.to_account_info(),
+                        to: ctx.accounts.vault.to_account_info(),
+                        authority: ctx.accounts.ramper.to_account_info(),
+                    },
+                ),
+                args.amount,
+                ctx.accounts.mint.decimals,
+            )?;
+            ctx.accounts.vault.reload()?;
+            ctx.accounts.vault.amount - vault_amount
+        } else {
+            // The withdrawal is only credited once the proof shows it was applied on L2
+            if ctx.accounts.platform.withdraw + args.lamports > ctx.accounts.platform.deposit
+                || args.amount > ctx.accounts.vault.amount
+            {
+                return Err(PlatformError::InsufficientDeposits.into());
+            }
+
+            ctx.accounts.ramp.current_state_hash = ctx.accounts.platform.last_state_hash;
+            args.amount
+        };
+
+        // Tokens have no common unit, the minimum depends on the decimals
+        if amount < min_token_ramp_amount(ctx.accounts.mint.decimals) {
+            return Err(PlatformError::RampAmountTooSmall.into());
+        }
+
+        ctx.accounts.platform.push_ramp_tx(
+            &mut ctx.accounts.ramp_queue_page,
+            RampTx {
+                is_onramp: args.is_onramp,
+                amount: args.lamports,
+                user: ctx.accounts.ramper.key(),
+                token: Some(TokenAmount {
+                    mint: ctx.accounts.mint.key(),
+                    decimals: ctx.accounts.mint.decimals,
+                    amount,
+                }),
+            },
+        )
+    }
+}
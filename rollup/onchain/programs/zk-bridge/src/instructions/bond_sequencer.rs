@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+
+use crate::errors::PlatformError;
+use crate::state::platform::Platform;
+use crate::state::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct BondSequencerArgs {
+    pub amount: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(args: BondSequencerArgs)]
+pub struct BondSequencer<'info> {
+    #[account(mut)]
+    pub sequencer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            PLATFORM_SEED_PREFIX,
+            platform.id.as_ref(),
+        ],
+        bump,
+        constraint = platform.sequencer == sequencer.key() @ PlatformError::InvalidSequencer
+    )]
+    pub platform: Account<'info, Platform>,
+    pub system_program: Program<'info, System>,
+}
+
+impl BondSequencer<'_> {
+    /// Adds lamports to the bond of the sequencer, which must bond again after
+    /// being slashed
+    pub fn handle(ctx: Context<Self>, args: BondSequencerArgs) -> Result<()> {
+        transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.sequencer.to_account_info(),
+                    to: ctx.accounts.platform.to_account_info(),
+                },
+            ),
+            args.amount,
+        )?;
+        ctx.accounts.platform.bond += args.amount;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::PlatformError;
+use crate::state::platform::Platform;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct ClaimFees<'info> {
+    #[account(mut)]
+    pub sequencer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            PLATFORM_SEED_PREFIX,
+            platform.id.as_ref(),
+        ],
+        bump,
+        constraint = platform.sequencer == sequencer.key() @ PlatformError::InvalidSequencer
+    )]
+    pub platform: Account<'info, Platform>,
+}
+
+impl ClaimFees<'_> {
+    /// Moves the ramp tx fees collected by the platform to the sequencer
+    pub fn handle(ctx: Context<Self>) -> Result<()> {
+        let fees = std::mem::take(&mut ctx.accounts.platform.fees);
+        ctx.accounts.platform.sub_lamports(fees)?;
+        ctx.accounts.sequencer.add_lamports(fees)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::errors::PlatformError;
+use crate::instructions::ClaimWithdrawalArgs;
+use crate::state::nullifier::Nullifier;
+use crate::state::platform::Platform;
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(args: ClaimWithdrawalArgs)]
+pub struct ClaimTokenWithdrawal<'info> {
+    #[account(mut)]
+    pub ramper: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            PLATFORM_SEED_PREFIX,
+            platform.id.as_ref(),
+        ],
+        bump
+    )]
+    pub platform: Account<'info, Platform>,
+    /// Fails to be created if the receipt was already claimed
+    #[account(
+        init,
+        payer = ramper,
+        space = 8 + Nullifier::INIT_SPACE,
+        seeds = [
+            NULLIFIER_SEED_PREFIX,
+            platform.id.as_ref(),
+            args.receipt.as_ref(),
+        ],
+        bump
+    )]
+    pub nullifier: Account<'info, Nullifier>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program,
+        seeds = [
+            VAULT_SEED_PREFIX,
+            platform.id.as_ref(),
+            mint.key().as_ref(),
+        ],
+        bump
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program,
+    )]
+    pub ramper_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl ClaimTokenWithdrawal<'_> {
+    pub fn handle(ctx: Context<Self>, args: ClaimWithdrawalArgs) -> Result<()> {
+        let receipt = args.verify_receipt(&ctx.accounts.platform, &ctx.accounts.ramper.key())?;
+        let token = receipt
+            .token
+            .filter(|token| token.mint == ctx.accounts.mint.key())
+            .ok_or(PlatformError::InvalidWithdrawalReceipt)?;
+
+        ctx.accounts.nullifier.bump = ctx.bumps.nullifier;
+
+        // The lamports were accounted for in `platform.withdraw` when the batch was proven
+        if receipt.amount > 0 {
+            ctx.accounts.platform.sub_lamports(receipt.amount)?;
+            ctx.accounts.ramper.add_lamports(receipt.amount)?;
+        }
+
+        let platform = &ctx.accounts.platform;
+        transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.vault.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.ramper_token_account.to_account_info(),
+                    authority: platform.to_account_info(),
+                },
+                &[&[PLATFORM_SEED_PREFIX, platform.id.as_ref(), &[platform.bump]]],
+            ),
+            token.amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::PlatformError;
+use crate::state::nullifier::Nullifier;
+use crate::state::platform::Platform;
+use crate::state::*;
+use crate::utils::{
+    SerializableAccount, SparseMerkleProof, WithdrawalReceipt, WITHDRAWAL_RECEIPT_OWNER,
+};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ClaimWithdrawalArgs {
+    /// Address of the receipt in the rollup state
+    pub receipt: Pubkey,
+    /// Receipt account as stored in the rollup state
+    pub account: SerializableAccount,
+    /// Inclusion proof of the receipt account against the last state hash
+    pub proof: SparseMerkleProof,
+}
+
+impl ClaimWithdrawalArgs {
+    /// Checks that the receipt of `user` is in the last proven state and
+    /// returns it
+    pub fn verify_receipt(&self, platform: &Platform, user: &Pubkey) -> Result<WithdrawalReceipt> {
+        if self.account.owner != WITHDRAWAL_RECEIPT_OWNER {
+            return Err(PlatformError::InvalidWithdrawalReceipt.into());
+        }
+        let receipt = WithdrawalReceipt::try_from_slice(&self.account.data)
+            .map_err(|_| PlatformError::InvalidWithdrawalReceipt)?;
+        if receipt.user != *user || receipt.address() != self.receipt {
+            return Err(PlatformError::InvalidWithdrawalReceipt.into());
+        }
+
+        if !self.proof.verify_inclusion(
+            &platform.last_state_hash,
+            &self.receipt,
+            &self.account.hash(),
+        ) {
+            return Err(PlatformError::InvalidStateProof.into());
+        }
+
+        Ok(receipt)
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(args: ClaimWithdrawalArgs)]
+pub struct ClaimWithdrawal<'info> {
+    #[account(mut)]
+    pub ramper: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            PLATFORM_SEED_PREFIX,
+            platform.id.as_ref(),
+        ],
+        bump
+    )]
+    pub platform: Account<'info, Platform>,
+    /// Fails to be created if the receipt was already claimed
+    #[account(
+        init,
+        payer = ramper,
+        space = 8 + Nullifier::INIT_SPACE,
+        seeds = [
+            NULLIFIER_SEED_PREFIX,
+            platform.id.as_ref(),
+            args.receipt.as_ref(),
+        ],
+        bump
+    )]
+    pub nullifier: Account<'info, Nullifier>,
+    pub system_program: Program<'info, System>,
+}
+
+impl ClaimWithdrawal<'_> {
+    pub fn handle(ctx: Context<Self>, args: ClaimWithdrawalArgs) -> Result<()> {
+        let receipt = args.verify_receipt(&ctx.accounts.platform, &ctx.accounts.ramper.key())?;
+        // Tokens are claimed with claim_token_withdrawal
+        if receipt.token.is_some() {
+            return Err(PlatformError::InvalidWithdrawalReceipt.into());
+        }
+
+        ctx.accounts.nullifier.bump = ctx.bumps.nullifier;
+
+        // The amount was accounted for in `platform.withdraw` when the batch was proven
+        ctx.accounts.platform.sub_lamports(receipt.amount)?;
+        ctx.accounts.ramper.add_lamports(receipt.amount)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+use crate::state::platform::Platform;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct CloseProof<'info> {
+    #[account(mut)]
+    pub prover: Signer<'info>,
+    #[account(
+        mut,
+        close = prover,
+        seeds = [
+            PROOF_SEED_PREFIX,
+            platform.id.as_ref(),
+            prover.key().as_ref(),
+        ],
+        bump
+    )]
+    pub proof: Account<'info, Proof>,
+    #[account(
+        seeds = [
+            PLATFORM_SEED_PREFIX,
+            platform.id.as_ref(),
+        ],
+        bump
+    )]
+    pub platform: Account<'info, Platform>,
+}
+
+impl CloseProof<'_> {
+    /// Discards a proof upload, returning its rent to the prover
+    pub fn handle(_ctx: Context<Self>) -> Result<()> {
+        Ok(())
+    }
+}
@@ -2,13 +2,24 @@ use anchor_lang::prelude::*;
 // use anchor_spl::associated_token::AssociatedToken;
 // use anchor_spl::token::*;
 
-use crate::state::{platform::Platform, PLATFORM_SEED_PREFIX};
+use anchor_lang::system_program::{transfer, Transfer};
+
+use crate::errors::PlatformError;
+use crate::state::{
+    platform::Platform, MIN_ESCAPE_HATCH_SLOTS, MIN_SEQUENCER_BOND, PLATFORM_SEED_PREFIX,
+};
 // use crate::state::*;
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct CreatePlatformArgs {
     pub id: Pubkey,
     pub initial_state_hash: [u8; 32],
+    /// Lamports bonded by the sequencer, at least [MIN_SEQUENCER_BOND]
+    pub bond: u64,
+    /// Number of L1 slots without a proof after which the platform can be
+    /// frozen, letting users withdraw from the last proven state. At least
+    /// [MIN_ESCAPE_HATCH_SLOTS].
+    pub escape_hatch_slots: u64,
 }
 
 #[derive(Accounts)]
@@ -32,14 +43,46 @@ pub struct CreatePlatform<'info> {
 
 impl CreatePlatform<'_> {
     pub fn handle(ctx: Context<Self>, args: CreatePlatformArgs) -> Result<()> {
+        if args.bond < MIN_SEQUENCER_BOND {
+            return Err(PlatformError::InsufficientBond.into());
+        }
+        if args.escape_hatch_slots < MIN_ESCAPE_HATCH_SLOTS {
+            return Err(PlatformError::EscapeHatchTooShort.into());
+        }
+        transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.sequencer.to_account_info(),
+                    to: ctx.accounts.platform.to_account_info(),
+                },
+            ),
+            args.bond,
+        )?;
+
         ctx.accounts.platform.set_inner(Platform {
             bump: ctx.bumps.platform,
             id: args.id,
             sequencer: ctx.accounts.sequencer.key(),
+            bond: args.bond,
+            fees: 0,
+            pending_sequencer: None,
+            rotation_timestamp: 0,
+            pending_since: 0,
             last_state_hash: args.initial_state_hash,
-            ramp_txs: vec![],
+            batch_number: 0,
+            ramp_queue_head: 0,
+            ramp_queue_tail: 0,
+            forced_txs_head: 0,
+            forced_txs_count: 0,
+            forced_txs_hash: [0; 32],
             deposit: 0,
             withdraw: 0,
+            last_slot: 0,
+            last_timestamp: 0,
+            last_proof_slot: Clock::get()?.slot,
+            escape_hatch_slots: args.escape_hatch_slots,
+            frozen: false,
         });
 
         Ok(())
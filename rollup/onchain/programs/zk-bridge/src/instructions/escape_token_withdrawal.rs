@@ -0,0 +1,159 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::errors::PlatformError;
+use crate::instructions::EscapeWithdrawalArgs;
+use crate::state::nullifier::Nullifier;
+use crate::state::platform::Platform;
+use crate::state::*;
+use crate::utils::mirrored_mint_address;
+
+/// Size of an SPL Token account, its mint, owner and amount coming first.
+/// Mirrored mints are created on L2 under the SPL Token program, so their
+/// tokens are never held by Token-2022 accounts, which can be larger.
+const TOKEN_ACCOUNT_LEN: usize = 165;
+
+#[derive(Accounts)]
+#[instruction(args: EscapeWithdrawalArgs)]
+pub struct EscapeTokenWithdrawal<'info> {
+    #[account(mut)]
+    pub ramper: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            PLATFORM_SEED_PREFIX,
+            platform.id.as_ref(),
+        ],
+        bump,
+        constraint = platform.frozen @ PlatformError::PlatformNotFrozen
+    )]
+    pub platform: Account<'info, Platform>,
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program,
+        seeds = [
+            VAULT_SEED_PREFIX,
+            platform.id.as_ref(),
+            mint.key().as_ref(),
+        ],
+        bump
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program,
+    )]
+    pub ramper_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// Fails to be created if the token account was already withdrawn
+    #[account(
+        init,
+        payer = ramper,
+        space = 8 + Nullifier::INIT_SPACE,
+        seeds = [
+            ESCAPE_TOKEN_SEED_PREFIX,
+            platform.id.as_ref(),
+            args.address.as_ref(),
+            mint.key().as_ref(),
+        ],
+        bump
+    )]
+    pub nullifier: Account<'info, Nullifier>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> EscapeTokenWithdrawal<'info> {
+    /// Withdraws the mirrored tokens of a token account of the ramper in the
+    /// last proven state, and its lamports, along with its token on-ramps of
+    /// the mint that were never proven. The address of an account missing from
+    /// the state can be given to only withdraw the on-ramps.
+    ///
+    /// The remaining accounts must be the [RampQueuePage]s holding the ramp
+    /// txs waiting for a proof.
+    pub fn handle(
+        ctx: Context<'_, '_, 'info, 'info, Self>,
+        args: EscapeWithdrawalArgs,
+    ) -> Result<()> {
+        args.verify_account(&ctx.accounts.platform)?;
+
+        let ramper = ctx.accounts.ramper.key();
+        let mint = ctx.accounts.mint.key();
+        let (balance, mut tokens) = match &args.account {
+            Some(account) => {
+                let data = &account.data;
+                if account.owner != anchor_spl::token::ID
+                    || data.len() != TOKEN_ACCOUNT_LEN
+                    || data[..32] != mirrored_mint_address(&mint).to_bytes()
+                    || data[32..64] != ramper.to_bytes()
+                {
+                    return Err(PlatformError::InvalidEscapeAccount.into());
+                }
+                let amount = u64::from_le_bytes(data[64..72].try_into().unwrap());
+                (account.lamports, amount)
+            }
+            None => (0, 0),
+        };
+
+        ctx.accounts.nullifier.bump = ctx.bumps.nullifier;
+
+        let platform = &mut ctx.accounts.platform;
+        platform.withdraw += balance;
+        if platform.withdraw > platform.deposit {
+            return Err(PlatformError::InsufficientDeposits.into());
+        }
+
+        // The deposits never reached the rollup, refund them
+        let (mut queue, _) = RampQueue::load(
+            platform,
+            ctx.remaining_accounts,
+            platform.ramp_queue_head,
+            platform.ramp_queue_tail,
+        )?;
+        let mut refund = 0;
+        for position in platform.ramp_queue_head..platform.ramp_queue_tail {
+            let ramp_tx = queue
+                .get_mut(position)
+                .ok_or(PlatformError::InvalidRampQueuePage)?;
+            if !ramp_tx.is_onramp || ramp_tx.user != ramper {
+                continue;
+            }
+            if let Some(token) = ramp_tx.token.as_mut().filter(|token| token.mint == mint) {
+                tokens += std::mem::take(&mut token.amount);
+                refund += std::mem::take(&mut ramp_tx.amount);
+            }
+        }
+        for page in queue.pages {
+            page.exit(&crate::ID)?;
+        }
+        platform.deposit -= refund;
+
+        if balance + refund > 0 {
+            ctx.accounts.platform.sub_lamports(balance + refund)?;
+            ctx.accounts.ramper.add_lamports(balance + refund)?;
+        }
+
+        let platform = &ctx.accounts.platform;
+        transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.vault.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.ramper_token_account.to_account_info(),
+                    authority: platform.to_account_info(),
+                },
+                &[&[PLATFORM_SEED_PREFIX, platform.id.as_ref(), &[platform.bump]]],
+            ),
+            tokens,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        Ok(())
+    }
+}
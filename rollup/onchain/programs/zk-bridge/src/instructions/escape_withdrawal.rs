@@ -0,0 +1,117 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::PlatformError;
+use crate::state::nullifier::Nullifier;
+use crate::state::platform::Platform;
+use crate::state::*;
+use crate::utils::{SerializableAccount, SparseMerkleProof};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct EscapeWithdrawalArgs {
+    /// Address of the account in the rollup state
+    pub address: Pubkey,
+    /// Account as stored in the rollup state, none if there is no account at
+    /// the address
+    pub account: Option<SerializableAccount>,
+    /// Inclusion or exclusion proof of the account against the last state hash
+    pub proof: SparseMerkleProof,
+}
+
+impl EscapeWithdrawalArgs {
+    /// Checks that the account is the one at `address` in the last proven state
+    pub fn verify_account(&self, platform: &Platform) -> Result<()> {
+        let hash = self.account.as_ref().map(SerializableAccount::hash);
+        if !self
+            .proof
+            .verify(&platform.last_state_hash, &self.address, hash.as_ref())
+        {
+            return Err(PlatformError::InvalidStateProof.into());
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(args: EscapeWithdrawalArgs)]
+pub struct EscapeWithdrawal<'info> {
+    #[account(mut)]
+    pub ramper: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            PLATFORM_SEED_PREFIX,
+            platform.id.as_ref(),
+        ],
+        bump,
+        constraint = platform.frozen @ PlatformError::PlatformNotFrozen
+    )]
+    pub platform: Account<'info, Platform>,
+    /// Fails to be created if the account was already withdrawn
+    #[account(
+        init,
+        payer = ramper,
+        space = 8 + Nullifier::INIT_SPACE,
+        seeds = [
+            ESCAPE_SEED_PREFIX,
+            platform.id.as_ref(),
+            args.address.as_ref(),
+        ],
+        bump
+    )]
+    pub nullifier: Account<'info, Nullifier>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> EscapeWithdrawal<'info> {
+    /// Withdraws the lamports of the account of the ramper in the last proven
+    /// state, along with the lamports of its on-ramps that were never proven.
+    /// Tokens are withdrawn with escape_token_withdrawal.
+    ///
+    /// The remaining accounts must be the [RampQueuePage]s holding the ramp
+    /// txs waiting for a proof.
+    pub fn handle(
+        ctx: Context<'_, '_, 'info, 'info, Self>,
+        args: EscapeWithdrawalArgs,
+    ) -> Result<()> {
+        if args.address != ctx.accounts.ramper.key() {
+            return Err(PlatformError::InvalidEscapeAccount.into());
+        }
+        args.verify_account(&ctx.accounts.platform)?;
+
+        ctx.accounts.nullifier.bump = ctx.bumps.nullifier;
+
+        let platform = &mut ctx.accounts.platform;
+        let balance = args.account.map_or(0, |account| account.lamports);
+        platform.withdraw += balance;
+        if platform.withdraw > platform.deposit {
+            return Err(PlatformError::InsufficientDeposits.into());
+        }
+
+        // The deposits never reached the rollup, refund them
+        let (mut queue, _) = RampQueue::load(
+            platform,
+            ctx.remaining_accounts,
+            platform.ramp_queue_head,
+            platform.ramp_queue_tail,
+        )?;
+        let mut refund = 0;
+        for position in platform.ramp_queue_head..platform.ramp_queue_tail {
+            let ramp_tx = queue
+                .get_mut(position)
+                .ok_or(PlatformError::InvalidRampQueuePage)?;
+            if ramp_tx.is_onramp && ramp_tx.user == args.address && ramp_tx.token.is_none() {
+                refund += std::mem::take(&mut ramp_tx.amount);
+            }
+        }
+        for page in queue.pages {
+            page.exit(&crate::ID)?;
+        }
+        platform.deposit -= refund;
+
+        ctx.accounts.platform.sub_lamports(balance + refund)?;
+        ctx.accounts.ramper.add_lamports(balance + refund)?;
+
+        Ok(())
+    }
+}
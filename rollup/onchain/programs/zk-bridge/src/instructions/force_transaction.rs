@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::PlatformError;
+use crate::state::platform::Platform;
+use crate::state::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ForceTransactionArgs {
+    /// Bincode serialized `VersionedTransaction`, executed on the rollup like
+    /// any other. It should use a durable nonce, as its blockhash may have
+    /// expired by the time it is executed.
+    pub tx: Vec<u8>,
+}
+
+#[derive(Accounts)]
+#[instruction(args: ForceTransactionArgs)]
+pub struct ForceTransaction<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            PLATFORM_SEED_PREFIX,
+            platform.id.as_ref(),
+        ],
+        bump,
+        constraint = !platform.frozen @ PlatformError::PlatformFrozen
+    )]
+    pub platform: Account<'info, Platform>,
+    #[account(
+        init,
+        payer = user,
+        space = 8 + ForcedTx::INIT_SPACE + args.tx.len(),
+        seeds = [
+            FORCED_TX_SEED_PREFIX,
+            platform.id.as_ref(),
+            &platform.forced_txs_count.to_le_bytes(),
+        ],
+        bump
+    )]
+    pub forced_tx: Account<'info, ForcedTx>,
+    pub system_program: Program<'info, System>,
+}
+
+impl ForceTransaction<'_> {
+    /// Queues the transaction, which proofs are rejected without once it was
+    /// queued for [FORCED_TX_INCLUSION_DELAY]
+    pub fn handle(ctx: Context<Self>, args: ForceTransactionArgs) -> Result<()> {
+        if args.tx.is_empty() || args.tx.len() > MAX_FORCED_TX_SIZE {
+            return Err(PlatformError::InvalidForcedTx.into());
+        }
+
+        let platform = &mut ctx.accounts.platform;
+        let index = platform.push_forced_tx(&args.tx)?;
+        ctx.accounts.forced_tx.set_inner(ForcedTx {
+            bump: ctx.bumps.forced_tx,
+            index,
+            timestamp: Clock::get()?.unix_timestamp,
+            hash: platform.forced_txs_hash,
+            tx: args.tx,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::PlatformError;
+use crate::state::platform::Platform;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct FreezePlatform<'info> {
+    pub reporter: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            PLATFORM_SEED_PREFIX,
+            platform.id.as_ref(),
+        ],
+        bump,
+        constraint = !platform.frozen @ PlatformError::PlatformFrozen
+    )]
+    pub platform: Account<'info, Platform>,
+}
+
+impl FreezePlatform<'_> {
+    /// Freezes the platform once no proof was submitted for
+    /// `platform.escape_hatch_slots` while ramp or forced txs wait for one past
+    /// the deadline. The last proven state is then final.
+    pub fn handle(ctx: Context<Self>) -> Result<()> {
+        let clock = Clock::get()?;
+        let platform = &ctx.accounts.platform;
+        if !platform.is_escape_hatch_open(clock.slot) {
+            return Err(PlatformError::EscapeHatchClosed.into());
+        }
+        // A platform nobody uses doesn't need proofs, it can't be frozen
+        if !platform.is_batch_overdue(clock.unix_timestamp) {
+            return Err(PlatformError::BatchNotOverdue.into());
+        }
+
+        ctx.accounts.platform.frozen = true;
+
+        Ok(())
+    }
+}
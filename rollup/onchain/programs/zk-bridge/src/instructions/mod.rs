@@ -1,14 +1,56 @@
+pub mod accept_sequencer;
+pub use accept_sequencer::*;
+
 pub mod add_ramp_tx;
 pub use add_ramp_tx::*;
 
+pub mod add_token_ramp_tx;
+pub use add_token_ramp_tx::*;
+
+pub mod bond_sequencer;
+pub use bond_sequencer::*;
+
+pub mod claim_fees;
+pub use claim_fees::*;
+
+pub mod claim_token_withdrawal;
+pub use claim_token_withdrawal::*;
+
+pub mod claim_withdrawal;
+pub use claim_withdrawal::*;
+
+pub mod close_proof;
+pub use close_proof::*;
+
 pub mod create_platform;
 pub use create_platform::*;
 
+pub mod escape_token_withdrawal;
+pub use escape_token_withdrawal::*;
+
+pub mod escape_withdrawal;
+pub use escape_withdrawal::*;
+
+pub mod force_transaction;
+pub use force_transaction::*;
+
+pub mod freeze_platform;
+pub use freeze_platform::*;
+
 pub mod prove;
 pub use prove::*;
 
+pub mod rotate_sequencer;
+pub use rotate_sequencer::*;
+
+pub mod slash_sequencer;
+pub use slash_sequencer::*;
+
 pub mod upload_proof;
 pub use upload_proof::*;
 
+pub mod verify_receipt;
+pub use verify_receipt::*;
+
 pub mod withdraw;
 pub use withdraw::*;
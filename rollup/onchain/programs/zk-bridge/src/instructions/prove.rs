@@ -1,30 +1,37 @@
 use crate::errors::PlatformError;
 use crate::state::platform::Platform;
+use crate::state::ramp::Ramp;
 use crate::state::*;
-use crate::utils::SP1Groth16Proof;
+use crate::utils::{hash_ramp_results, BlockHeader, FeeConfig, SP1Groth16Proof};
 use anchor_lang::prelude::*;
 use verifier::verify_proof;
 
-/// Derived as follows:
-///
-/// ```
-/// let client = sp1_sdk::ProverClient::new();
-/// let (pk, vk) = client.setup(YOUR_ELF_HERE);
-/// let vkey_hash = vk.bytes32();
-/// ```
-const ZK_BRIDGE_VKEY_HASH: &str =
-    "0x004cd8a01c6575b6d58e193d1a8fee5917a96b6e2162ec60163dc7686b2811cb";
-// "0x0064e652e64b5b61fc6090d231016260c1ff2ba3746bd7661356a0d780fa0162";
-// "0x0039a2ea684d5ebf650341d23f14c448a552e72793827ffe9c54aca424224761";
-// "0x00b5f4f8596951753342637e0ab298e2072459a9aa8ad51116290b32d9206a55";
+/// Hash of the verifying key of the zk program, written by the `prove` command
+/// of the script along with the test proof. `vk-hash` checks it against the
+/// program.
+const ZK_BRIDGE_VKEY_HASH: &str = include_str!("../../../../../zk/script/vk_hash.txt");
+
+/// How far in the future of L1 time a batch timestamp may be, in seconds
+const MAX_TIMESTAMP_DRIFT: i64 = 60;
+
+/// Data of the batch the public values of the proof only commit to
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ProveArgs {
+    pub header: BlockHeader,
+    pub fees: FeeConfig,
+    /// Whether each ramp tx of the batch, taken from the head of the queue, was
+    /// applied to the rollup state
+    pub ramp_results: Vec<bool>,
+}
 
 #[derive(Accounts)]
 pub struct Prove<'info> {
     #[account(mut)]
     pub prover: Signer<'info>,
-    /// CHECK: The proof is verified
-    // pub proof: UncheckedAccount<'info>,
+    /// Closed once the batch is applied, returning its rent to the prover
     #[account(
+        mut,
+        close = prover,
         seeds = [
             PROOF_SEED_PREFIX,
             platform.id.as_ref(),
@@ -39,15 +46,37 @@ pub struct Prove<'info> {
             PLATFORM_SEED_PREFIX,
             platform.id.as_ref(),
         ],
-        bump
+        bump,
+        constraint = platform.sequencer == prover.key() @ PlatformError::InvalidSequencer,
+        constraint = !platform.frozen @ PlatformError::PlatformFrozen
     )]
     pub platform: Account<'info, Platform>,
+    /// Keeps the receipts root of the batch
+    #[account(
+        init,
+        payer = prover,
+        space = 8 + BatchReceipts::INIT_SPACE,
+        seeds = [
+            BATCH_RECEIPTS_SEED_PREFIX,
+            platform.id.as_ref(),
+            &(platform.batch_number + 1).to_le_bytes(),
+        ],
+        bump
+    )]
+    pub batch_receipts: Account<'info, BatchReceipts>,
     pub system_program: Program<'info, System>,
 }
 
-impl Prove<'_> {
-    pub fn handle(ctx: Context<Self>) -> Result<()> {
+impl<'info> Prove<'info> {
+    /// The remaining accounts must be the [RampQueuePage]s holding the proven
+    /// ramp txs, followed by the [ForcedTx]s executed by the batch and the one
+    /// after them if any, then by the [Ramp] accounts of the users of every
+    /// on-ramp that failed on L2, in the order of the ramp txs, so that their
+    /// deposit is refunded. Applied off-ramps are claimed with their withdrawal
+    /// receipt instead, as are the tokens of failed token on-ramps.
+    pub fn handle(ctx: Context<'_, '_, 'info, 'info, Self>, args: ProveArgs) -> Result<()> {
         // Taking data from an account because it's too big to fit in an instruction
+        ctx.accounts.proof.verify()?;
         let proof: SP1Groth16Proof =
             AnchorDeserialize::deserialize(&mut ctx.accounts.proof.data.as_slice())?;
         let commited_values = &proof.sp1_public_inputs;
@@ -60,27 +89,173 @@ impl Prove<'_> {
         )
         .map_err(|_| PlatformError::InvalidProof)?;
 
-        // Check that ramps txs match the ones in the platform
-        // Currently only check the count, could be improved to a hash of all txs
-        if commited_values.input.ramp_txs.len() != ctx.accounts.platform.ramp_txs.len() {
-            return Err(PlatformError::MissingRampTxs.into());
+        // A slashed sequencer must bond again before proving
+        if ctx.accounts.platform.bond < MIN_SEQUENCER_BOND {
+            return Err(PlatformError::InsufficientBond.into());
+        }
+
+        // The batch must be executed on the last proven state, so that proven
+        // batches form a chain of transitions from the initial state
+        if commited_values.prior_state_root != ctx.accounts.platform.last_state_hash {
+            return Err(PlatformError::InvalidStateHash.into());
+        }
+        if Some(commited_values.batch_number) != ctx.accounts.platform.batch_number.checked_add(1) {
+            return Err(PlatformError::InvalidBatchNumber.into());
         }
 
-        // Empty pending ramp txs
-        ctx.accounts.platform.ramp_txs = vec![];
+        // Batches must move forward, and not ahead of L1 time
+        let now = Clock::get()?.unix_timestamp;
+        let header = &args.header;
+        if header.hash(&args.fees)? != commited_values.header_hash {
+            return Err(PlatformError::InvalidBlockHeader.into());
+        }
+        if header.slot <= ctx.accounts.platform.last_slot
+            || header.unix_timestamp < ctx.accounts.platform.last_timestamp
+            || header.unix_timestamp > now + MAX_TIMESTAMP_DRIFT
+        {
+            return Err(PlatformError::InvalidBlockHeader.into());
+        }
 
-        // This can currently brick the platform, there should be a limit in number of ramp txs
-        for ramp_tx in commited_values
-            .input
-            .ramp_txs
+        // The batch consumes ramp txs from the head of the queue. The queue may
+        // have grown since the sequencer read it, so the batch can end before
+        // its tail.
+        let platform = &ctx.accounts.platform;
+        let start = platform.ramp_queue_head;
+        let end = start + args.ramp_results.len() as u64;
+        if end > platform.ramp_queue_tail {
+            return Err(PlatformError::InvalidRampQueueRange.into());
+        }
+        let (queue, accounts) = RampQueue::load(platform, ctx.remaining_accounts, start, end)?;
+        let ramp_txs = (start..end)
+            .map(|position| queue.get(position).cloned())
+            .collect::<Option<Vec<_>>>()
+            .ok_or(PlatformError::InvalidRampQueuePage)?;
+
+        // Check that ramps txs are exactly the ones queued in the platform
+        let ramp_txs_hash = ramp_txs
             .iter()
-            .filter(|ramp_tx| !ramp_tx.is_onramp)
+            .try_fold([0; 32], |hash, ramp_tx| ramp_tx.chain(&hash))?;
+        if commited_values.ramp_txs_hash != ramp_txs_hash {
+            return Err(PlatformError::InvalidRampTxsHash.into());
+        }
+        if hash_ramp_results(&args.ramp_results)? != commited_values.ramp_results_hash {
+            return Err(PlatformError::InvalidRampResults.into());
+        }
+
+        // The batch executes the forced txs from the first one waiting for a
+        // proof, whatever their result. The hash chain recorded when the last
+        // of them was forced shows that they are the ones queued.
+        let forced_start = platform.forced_txs_head;
+        let forced_end = forced_start
+            .checked_add(commited_values.forced_txs_count)
+            .filter(|end| {
+                commited_values.forced_txs_start == forced_start
+                    && *end <= platform.forced_txs_count
+            })
+            .ok_or(PlatformError::InvalidForcedTxsRange)?;
+        let forced_accounts = (forced_end - forced_start) as usize
+            + usize::from(forced_end < platform.forced_txs_count);
+        if accounts.len() < forced_accounts {
+            return Err(PlatformError::MissingForcedTx.into());
+        }
+        let (forced_infos, ramp_accounts) = accounts.split_at(forced_accounts);
+        let mut forced_txs = (forced_start..)
+            .zip(forced_infos)
+            .map(|(index, info)| ForcedTx::load(platform, info, index))
+            .collect::<Result<Vec<_>>>()?;
+        let next_forced_tx = if forced_end < platform.forced_txs_count {
+            forced_txs.pop()
+        } else {
+            None
+        };
+        if forced_txs
+            .last()
+            .is_some_and(|forced_tx| forced_tx.hash != commited_values.forced_txs_hash)
+        {
+            return Err(PlatformError::InvalidForcedTxsHash.into());
+        }
+        // Forced txs queued long enough ago must all be executed, so that the
+        // sequencer can't censor them by proving batches without them
+        if next_forced_tx
+            .is_some_and(|forced_tx| now >= forced_tx.timestamp + FORCED_TX_INCLUSION_DELAY)
         {
-            ctx.accounts.platform.withdraw += ramp_tx.amount;
+            return Err(PlatformError::ForcedTxNotExecuted.into());
+        }
+
+        // Pop the proven ramp and forced txs. The sequencer has until the next
+        // deadline to prove the ones left in the queues.
+        let platform = &mut ctx.accounts.platform;
+        platform.ramp_queue_head = end;
+        platform.forced_txs_head = forced_end;
+        platform.pending_since =
+            if platform.pending_ramp_txs() == 0 && platform.pending_forced_txs() == 0 {
+                0
+            } else {
+                now
+            };
+
+        let mut ramp_accounts = ramp_accounts.iter();
+        for (ramp_tx, applied) in ramp_txs.iter().zip(args.ramp_results.iter()) {
+            match (ramp_tx.is_onramp, *applied) {
+                (false, true) => {
+                    // The lamports left the rollup, they can be claimed from the platform
+                    ctx.accounts.platform.withdraw += ramp_tx.amount;
+                    if ctx.accounts.platform.withdraw > ctx.accounts.platform.deposit {
+                        return Err(PlatformError::InsufficientDeposits.into());
+                    }
+                    continue;
+                }
+                (true, false) => {}
+                _ => continue,
+            }
+
+            let ramp_info = ramp_accounts
+                .next()
+                .ok_or(PlatformError::MissingRampAccount)?;
+            let mut ramp = Account::<Ramp>::try_from(ramp_info)?;
+            let ramp_key = Pubkey::create_program_address(
+                &[
+                    RAMP_SEED_PREFIX,
+                    ctx.accounts.platform.id.as_ref(),
+                    ramp_tx.user.as_ref(),
+                    &[ramp.bump],
+                ],
+                &crate::ID,
+            )
+            .map_err(|_| PlatformError::InvalidRampAccount)?;
+            if ramp_key != ramp_info.key() || ramp.ramper != ramp_tx.user {
+                return Err(PlatformError::InvalidRampAccount.into());
+            }
+
+            // The deposit never reached the rollup, refund it
+            ctx.accounts.platform.deposit -= ramp_tx.amount;
+            ramp.pending_withdraw += ramp_tx.amount;
+            ramp.current_state_hash = commited_values.output;
+            ramp.exit(&crate::ID)?;
         }
 
         // Update the platform state
         ctx.accounts.platform.last_state_hash = commited_values.output;
+        ctx.accounts.platform.batch_number = commited_values.batch_number;
+        ctx.accounts.platform.last_slot = header.slot;
+        ctx.accounts.platform.last_timestamp = header.unix_timestamp;
+        ctx.accounts.batch_receipts.set_inner(BatchReceipts {
+            bump: ctx.bumps.batch_receipts,
+            batch_number: commited_values.batch_number,
+            receipts_root: commited_values.receipts_root,
+        });
+        ctx.accounts.platform.last_proof_slot = Clock::get()?.slot;
+
+        // Pages are closed once all their ramp txs are proven, and forced txs
+        // once executed
+        for page in queue.pages {
+            if (page.index + 1) * RAMP_QUEUE_PAGE_SIZE <= end {
+                page.close(ctx.accounts.prover.to_account_info())?;
+            }
+        }
+        for forced_tx in forced_txs {
+            forced_tx.close(ctx.accounts.prover.to_account_info())?;
+        }
 
         Ok(())
     }
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::PlatformError;
+use crate::state::platform::Platform;
+use crate::state::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct RotateSequencerArgs {
+    pub new_sequencer: Pubkey,
+}
+
+#[derive(Accounts)]
+#[instruction(args: RotateSequencerArgs)]
+pub struct RotateSequencer<'info> {
+    /// The sequencer, or anyone once a slashed sequencer missed the next deadline
+    pub proposer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            PLATFORM_SEED_PREFIX,
+            platform.id.as_ref(),
+        ],
+        bump
+    )]
+    pub platform: Account<'info, Platform>,
+}
+
+impl RotateSequencer<'_> {
+    /// Proposes the next sequencer, which can take over after the rotation
+    /// delay. A new proposal replaces the pending one.
+    ///
+    /// A sequencer that was slashed and didn't bond again and prove by the
+    /// next deadline is considered gone: anyone can then propose a sequencer,
+    /// which can take over right away.
+    pub fn handle(ctx: Context<Self>, args: RotateSequencerArgs) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let platform = &mut ctx.accounts.platform;
+        let rotation_timestamp = if platform.sequencer == ctx.accounts.proposer.key() {
+            now + SEQUENCER_ROTATION_DELAY
+        } else if platform.bond < MIN_SEQUENCER_BOND && platform.is_batch_overdue(now) {
+            now
+        } else {
+            return Err(PlatformError::InvalidSequencer.into());
+        };
+        platform.pending_sequencer = Some(args.new_sequencer);
+        platform.rotation_timestamp = rotation_timestamp;
+
+        Ok(())
+    }
+}
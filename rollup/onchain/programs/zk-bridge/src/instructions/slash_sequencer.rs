@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::incinerator;
+
+use crate::errors::PlatformError;
+use crate::state::platform::Platform;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct SlashSequencer<'info> {
+    #[account(mut)]
+    pub reporter: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [
+            PLATFORM_SEED_PREFIX,
+            platform.id.as_ref(),
+        ],
+        bump,
+        constraint = platform.sequencer != reporter.key() @ PlatformError::SelfReport
+    )]
+    pub platform: Account<'info, Platform>,
+    /// CHECK: Receives the part of the bond that is burned
+    #[account(mut, address = incinerator::ID)]
+    pub incinerator: UncheckedAccount<'info>,
+}
+
+impl SlashSequencer<'_> {
+    /// Slashes the bond of the sequencer when ramp or forced txs have been
+    /// waiting for a proof past [BATCH_DEADLINE], rewarding the reporter with
+    /// a share of it and burning the rest
+    pub fn handle(ctx: Context<Self>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        if !ctx.accounts.platform.is_batch_overdue(now) {
+            return Err(PlatformError::BatchNotOverdue.into());
+        }
+
+        let bond = ctx.accounts.platform.bond;
+        let reward = bond / SLASH_REWARD_DIVISOR;
+        ctx.accounts.platform.sub_lamports(bond)?;
+        ctx.accounts.reporter.add_lamports(reward)?;
+        ctx.accounts.incinerator.add_lamports(bond - reward)?;
+
+        // The sequencer has until the next deadline to bond again and prove
+        let platform = &mut ctx.accounts.platform;
+        platform.bond = 0;
+        platform.pending_since = now;
+
+        Ok(())
+    }
+}
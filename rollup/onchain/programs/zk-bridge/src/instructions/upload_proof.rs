@@ -1,11 +1,16 @@
 use anchor_lang::prelude::*;
 
+use crate::errors::PlatformError;
 use crate::state::platform::Platform;
 use crate::state::*;
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct UploadProofArgs {
+    /// Size of the complete proof
     pub proof_size: u64,
+    /// Hash of the complete proof, checked before it is used
+    pub proof_hash: [u8; 32],
+    /// Offset of the chunks written, a multiple of [PROOF_CHUNK_SIZE]
     pub offset: u64,
     pub proof_data: Vec<u8>,
 }
@@ -18,7 +23,7 @@ pub struct UploadProof<'info> {
     #[account(
         init_if_needed,
         payer = prover,
-        space = 8 + Proof::INIT_SPACE + args.proof_size as usize,
+        space = 8 + Proof::INIT_SPACE + Proof::space(args.proof_size),
         seeds = [
             PROOF_SEED_PREFIX,
             platform.id.as_ref(),
@@ -33,25 +38,26 @@ pub struct UploadProof<'info> {
             PLATFORM_SEED_PREFIX,
             platform.id.as_ref(),
         ],
-        bump
+        bump,
+        constraint = platform.sequencer == prover.key() @ PlatformError::InvalidSequencer,
+        constraint = !platform.frozen @ PlatformError::PlatformFrozen
     )]
     pub platform: Account<'info, Platform>,
     pub system_program: Program<'info, System>,
 }
 
 impl UploadProof<'_> {
+    /// Writes chunks of the proof, in any order. An upload that went wrong is
+    /// discarded with close_proof.
     pub fn handle(ctx: Context<Self>, args: UploadProofArgs) -> Result<()> {
         let proof = &mut ctx.accounts.proof;
         if proof.bump != ctx.bumps.proof {
             proof.bump = ctx.bumps.proof;
-            proof.data = vec![0; args.proof_size as usize];
+            proof.start(args.proof_size, args.proof_hash);
+        } else if proof.size != args.proof_size || proof.hash != args.proof_hash {
+            return Err(PlatformError::ProofMismatch.into());
         }
 
-        let offset = args.offset as usize;
-        let end = (offset + args.proof_data.len()).min(proof.data.len());
-        msg!("offset: {}, end: {}", offset, end);
-        proof.data[offset..end].copy_from_slice(&args.proof_data);
-
-        Ok(())
+        proof.write(args.offset, &args.proof_data)
     }
 }
@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+
+use crate::state::platform::Platform;
+use crate::state::*;
+use crate::utils::ReceiptProof;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct VerifyReceiptArgs {
+    /// Borsh serialized `TransactionReceipt` of the runner
+    pub receipt: Vec<u8>,
+    /// Inclusion proof of the receipt in the receipts of the batch
+    pub proof: ReceiptProof,
+}
+
+#[derive(Accounts)]
+pub struct VerifyReceipt<'info> {
+    #[account(
+        seeds = [
+            PLATFORM_SEED_PREFIX,
+            platform.id.as_ref(),
+        ],
+        bump
+    )]
+    pub platform: Account<'info, Platform>,
+    #[account(
+        seeds = [
+            BATCH_RECEIPTS_SEED_PREFIX,
+            platform.id.as_ref(),
+            &batch_receipts.batch_number.to_le_bytes(),
+        ],
+        bump = batch_receipts.bump
+    )]
+    pub batch_receipts: Account<'info, BatchReceipts>,
+}
+
+impl VerifyReceipt<'_> {
+    /// Fails unless the receipt is one of the proven batch, so that programs
+    /// can act on the outcome of a rollup transaction through a CPI
+    pub fn handle(ctx: Context<Self>, args: VerifyReceiptArgs) -> Result<()> {
+        ctx.accounts
+            .batch_receipts
+            .verify(&args.receipt, &args.proof)
+    }
+}
@@ -18,6 +18,7 @@ pub struct Withdraw<'info> {
     #[account(mut)]
     pub ramper: Signer<'info>,
     #[account(
+        mut,
         seeds = [
             PLATFORM_SEED_PREFIX,
             platform.id.as_ref(),
@@ -38,16 +39,14 @@ pub struct Withdraw<'info> {
 }
 
 impl Withdraw<'_> {
-    pub fn validate(&self) -> Result<()> {
-        if self.ramp.current_state_hash != self.platform.last_state_hash {
-            return Err(PlatformError::InvalidStateHash.into());
-        }
-
-        Ok(())
-    }
-
+    /// Withdraws deposits refunded because they could not be applied on L2
     pub fn handle(ctx: Context<Self>, args: WithdrawArgs) -> Result<()> {
-        ctx.accounts.ramp.pending_withdraw -= args.amount;
+        ctx.accounts.ramp.pending_withdraw = ctx
+            .accounts
+            .ramp
+            .pending_withdraw
+            .checked_sub(args.amount)
+            .ok_or(PlatformError::InsufficientPendingWithdraw)?;
         ctx.accounts.platform.sub_lamports(args.amount)?;
         ctx.accounts.ramper.add_lamports(args.amount)?;
 
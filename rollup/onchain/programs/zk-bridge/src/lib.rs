@@ -17,23 +17,130 @@ pub mod zk_bridge {
         CreatePlatform::handle(ctx, args)
     }
 
-    #[access_control(ctx.accounts.validate())]
     pub fn withdraw(ctx: Context<Withdraw>, args: WithdrawArgs) -> Result<()> {
         Withdraw::handle(ctx, args)
     }
 
-    /// Add a ramp transaction to the platform.
-    ///
-    /// **This can currently be used to DoS the platform by adding transactions faster than the sequencer can generate proofs.**
+    /// Claim lamports withdrawn from the rollup, proving the withdrawal receipt
+    /// against the last proven state root.
+    pub fn claim_withdrawal(
+        ctx: Context<ClaimWithdrawal>,
+        args: ClaimWithdrawalArgs,
+    ) -> Result<()> {
+        ClaimWithdrawal::handle(ctx, args)
+    }
+
+    /// Claim SPL tokens withdrawn from the rollup, and the lamports withdrawn
+    /// along with them, proving the withdrawal receipt against the last proven
+    /// state root.
+    pub fn claim_token_withdrawal(
+        ctx: Context<ClaimTokenWithdrawal>,
+        args: ClaimWithdrawalArgs,
+    ) -> Result<()> {
+        ClaimTokenWithdrawal::handle(ctx, args)
+    }
+
+    /// Add a ramp transaction to the queue of the platform, for a fee. The
+    /// queue is capped so that a flood of ramp txs can't stall the proofs.
     pub fn add_ramp_tx(ctx: Context<AddRampTx>, args: AddRampTxArgs) -> Result<()> {
         AddRampTx::handle(ctx, args)
     }
 
+    /// Add a ramp transaction moving SPL tokens, which are locked in the
+    /// platform vault of their mint and mirrored by a mint on the rollup.
+    pub fn add_token_ramp_tx(ctx: Context<AddTokenRampTx>, args: AddTokenRampTxArgs) -> Result<()> {
+        AddTokenRampTx::handle(ctx, args)
+    }
+
+    /// Propose the next sequencer, which can take over after a delay, or right
+    /// away in place of a slashed sequencer that is gone.
+    pub fn rotate_sequencer(
+        ctx: Context<RotateSequencer>,
+        args: RotateSequencerArgs,
+    ) -> Result<()> {
+        RotateSequencer::handle(ctx, args)
+    }
+
+    /// Take over as the proposed sequencer, bonding lamports and returning the
+    /// bond and fees of the previous sequencer.
+    pub fn accept_sequencer(
+        ctx: Context<AcceptSequencer>,
+        args: AcceptSequencerArgs,
+    ) -> Result<()> {
+        AcceptSequencer::handle(ctx, args)
+    }
+
+    /// Add lamports to the bond of the sequencer.
+    pub fn bond_sequencer(ctx: Context<BondSequencer>, args: BondSequencerArgs) -> Result<()> {
+        BondSequencer::handle(ctx, args)
+    }
+
+    /// Claim the ramp tx fees collected by the platform.
+    pub fn claim_fees(ctx: Context<ClaimFees>) -> Result<()> {
+        ClaimFees::handle(ctx)
+    }
+
+    /// Slash the bond of the sequencer when ramp or forced txs wait for a proof
+    /// past the deadline, the reporter getting a share of it.
+    pub fn slash_sequencer(ctx: Context<SlashSequencer>) -> Result<()> {
+        SlashSequencer::handle(ctx)
+    }
+
+    /// Force the inclusion of a rollup transaction, which the next proof must
+    /// execute.
+    pub fn force_transaction(
+        ctx: Context<ForceTransaction>,
+        args: ForceTransactionArgs,
+    ) -> Result<()> {
+        ForceTransaction::handle(ctx, args)
+    }
+
+    /// Freeze the platform when no proof was submitted for too long, so that
+    /// users can withdraw from the last proven state.
+    pub fn freeze_platform(ctx: Context<FreezePlatform>) -> Result<()> {
+        FreezePlatform::handle(ctx)
+    }
+
+    /// Withdraw the lamports of an account from a frozen platform, proving it
+    /// against the last proven state root.
+    pub fn escape_withdrawal<'info>(
+        ctx: Context<'_, '_, 'info, 'info, EscapeWithdrawal<'info>>,
+        args: EscapeWithdrawalArgs,
+    ) -> Result<()> {
+        EscapeWithdrawal::handle(ctx, args)
+    }
+
+    /// Withdraw the SPL tokens of a token account from a frozen platform,
+    /// proving it against the last proven state root.
+    pub fn escape_token_withdrawal<'info>(
+        ctx: Context<'_, '_, 'info, 'info, EscapeTokenWithdrawal<'info>>,
+        args: EscapeWithdrawalArgs,
+    ) -> Result<()> {
+        EscapeTokenWithdrawal::handle(ctx, args)
+    }
+
+    /// Upload chunks of a proof, which must cover the whole proof and match its
+    /// hash before it can be used.
     pub fn upload_proof(ctx: Context<UploadProof>, args: UploadProofArgs) -> Result<()> {
         UploadProof::handle(ctx, args)
     }
 
-    pub fn prove(ctx: Context<Prove>) -> Result<()> {
-        Prove::handle(ctx)
+    /// Discard a proof upload.
+    pub fn close_proof(ctx: Context<CloseProof>) -> Result<()> {
+        CloseProof::handle(ctx)
+    }
+
+    /// Check that a transaction receipt is one of a proven batch.
+    pub fn verify_receipt(ctx: Context<VerifyReceipt>, args: VerifyReceiptArgs) -> Result<()> {
+        VerifyReceipt::handle(ctx, args)
+    }
+
+    /// Apply a proven batch. The proof only commits to the batch data, of which
+    /// the block header and the ramp results are passed in the arguments.
+    pub fn prove<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Prove<'info>>,
+        args: ProveArgs,
+    ) -> Result<()> {
+        Prove::handle(ctx, args)
     }
 }
@@ -0,0 +1,27 @@
+use crate::errors::PlatformError;
+use crate::utils::ReceiptProof;
+use anchor_lang::prelude::*;
+
+pub const BATCH_RECEIPTS_SEED_PREFIX: &[u8] = b"batch_receipts:";
+
+/// Root of the transaction receipts of a proven batch, created by `prove` at
+/// the number of the batch
+#[account]
+#[derive(Default, InitSpace)]
+pub struct BatchReceipts {
+    pub bump: u8,
+    pub batch_number: u64,
+    pub receipts_root: [u8; 32],
+}
+
+impl BatchReceipts {
+    /// Checks that the borsh serialized `TransactionReceipt` of the runner is
+    /// in the receipts of the batch
+    pub fn verify(&self, receipt: &[u8], proof: &ReceiptProof) -> Result<()> {
+        if !proof.verify(&self.receipts_root, receipt) {
+            return Err(PlatformError::InvalidReceiptProof.into());
+        }
+
+        Ok(())
+    }
+}
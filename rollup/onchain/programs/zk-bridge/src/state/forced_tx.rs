@@ -0,0 +1,56 @@
+use crate::errors::PlatformError;
+use crate::state::platform::Platform;
+use anchor_lang::prelude::*;
+
+pub const FORCED_TX_SEED_PREFIX: &[u8] = b"forced_tx:";
+
+/// Largest serialized transaction that can be forced, the size of a packet
+pub const MAX_FORCED_TX_SIZE: usize = 1232;
+/// Time after which a forced tx must be executed by every proof, in seconds,
+/// leaving the sequencer enough time to prove a batch including it
+pub const FORCED_TX_INCLUSION_DELAY: i64 = 10 * 60;
+
+/// Transaction submitted on L1, which a proof must execute once it was queued
+/// for long enough. It is kept so that the sequencer can read it back, and
+/// closed to the prover once executed.
+#[account]
+#[derive(Default, InitSpace)]
+pub struct ForcedTx {
+    pub bump: u8,
+    /// Position of the transaction among all the transactions forced on the platform
+    pub index: u64,
+    /// Time the transaction was forced
+    pub timestamp: i64,
+    /// Forced txs hash chain up to this transaction, which a proof executing
+    /// up to it must commit to
+    pub hash: [u8; 32],
+    /// Bincode serialized `VersionedTransaction`
+    #[max_len(0)]
+    pub tx: Vec<u8>,
+}
+
+impl ForcedTx {
+    /// Loads the forced tx at `index` of the platform from `info`
+    pub fn load<'info>(
+        platform: &Platform,
+        info: &'info AccountInfo<'info>,
+        index: u64,
+    ) -> Result<Account<'info, Self>> {
+        let forced_tx = Account::<Self>::try_from(info)?;
+        let forced_tx_key = Pubkey::create_program_address(
+            &[
+                FORCED_TX_SEED_PREFIX,
+                platform.id.as_ref(),
+                &index.to_le_bytes(),
+                &[forced_tx.bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| PlatformError::InvalidForcedTxAccount)?;
+        if forced_tx_key != info.key() {
+            return Err(PlatformError::InvalidForcedTxAccount.into());
+        }
+
+        Ok(forced_tx)
+    }
+}
@@ -1,7 +1,17 @@
+pub mod batch_receipts;
+pub mod forced_tx;
+pub mod nullifier;
 pub mod platform;
 pub mod proof;
 pub mod ramp;
+pub mod ramp_queue;
+pub mod vault;
 
+pub use batch_receipts::*;
+pub use forced_tx::*;
+pub use nullifier::*;
 pub use platform::*;
 pub use proof::*;
 pub use ramp::*;
+pub use ramp_queue::*;
+pub use vault::*;
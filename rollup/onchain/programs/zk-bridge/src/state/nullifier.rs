@@ -0,0 +1,15 @@
+use anchor_lang::prelude::*;
+
+pub const NULLIFIER_SEED_PREFIX: &[u8] = b"nullifier:";
+pub const ESCAPE_SEED_PREFIX: &[u8] = b"escape:";
+/// Token withdrawals from a frozen platform are nullified per address and
+/// mint, apart from the lamports withdrawals of the same addresses
+pub const ESCAPE_TOKEN_SEED_PREFIX: &[u8] = b"escape_token:";
+
+/// Marks a withdrawal receipt as claimed, or a rollup account as withdrawn
+/// from a frozen platform. It can only be created once.
+#[account]
+#[derive(Default, InitSpace)]
+pub struct Nullifier {
+    pub bump: u8,
+}
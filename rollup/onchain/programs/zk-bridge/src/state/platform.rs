@@ -1,20 +1,127 @@
-use crate::utils::RampTx;
+use crate::errors::PlatformError;
+use crate::state::{RampQueuePage, MAX_PENDING_RAMP_TXS, RAMP_QUEUE_PAGE_SIZE};
+use crate::utils::{chain_forced_tx, RampTx};
 use anchor_lang::prelude::*;
 
 pub const PLATFORM_SEED_PREFIX: &[u8] = b"platform:";
 
+/// Lamports the sequencer must have bonded to submit proofs
+pub const MIN_SEQUENCER_BOND: u64 = 1_000_000_000;
+/// Delay before a new sequencer can take over, in seconds
+pub const SEQUENCER_ROTATION_DELAY: i64 = 24 * 60 * 60;
+/// Time the sequencer has to prove the queued ramp and forced txs before its
+/// bond can be slashed, in seconds
+pub const BATCH_DEADLINE: i64 = 60 * 60;
+/// The reporter of an overdue batch gets the slashed bond divided by this, the
+/// rest being burned so that a sequencer gains nothing by reporting itself
+pub const SLASH_REWARD_DIVISOR: u64 = 10;
+/// Smallest number of L1 slots without a proof before a platform can be
+/// frozen, about a day
+pub const MIN_ESCAPE_HATCH_SLOTS: u64 = 216_000;
+
 /// A platform is the account storing state waiting to be sent to the rollup
 #[account]
 #[derive(Default, InitSpace)]
 pub struct Platform {
     pub bump: u8,
+    /// Only signer allowed to upload and submit proofs
     pub sequencer: Pubkey,
+    /// Lamports bonded by the sequencer, held by the platform on top of the
+    /// deposits. They are slashed when batches are withheld past the deadline.
+    pub bond: u64,
+    /// Ramp tx fees held by the platform until the sequencer claims them
+    pub fees: u64,
+    /// Sequencer taking over once `rotation_timestamp` is reached
+    pub pending_sequencer: Option<Pubkey>,
+    pub rotation_timestamp: i64,
+    /// Time since which ramp or forced txs wait for a proof, 0 if none are queued
+    pub pending_since: i64,
     pub id: Pubkey,
     pub last_state_hash: [u8; 32],
-    #[max_len(0)]
-    pub ramp_txs: Vec<RampTx>,
+    /// Number of the last proven batch, 0 until the first proof
+    pub batch_number: u64,
+    /// Position in the ramp txs queue of the first ramp tx waiting for a proof
+    pub ramp_queue_head: u64,
+    /// Number of ramp txs queued since the platform was created
+    pub ramp_queue_tail: u64,
+    /// Index of the first forced tx waiting for a proof
+    pub forced_txs_head: u64,
+    /// Number of transactions forced since the platform was created, each
+    /// stored in a [crate::state::ForcedTx] account at its index
+    pub forced_txs_count: u64,
+    /// Hash chain over every transaction forced since the platform was created
+    pub forced_txs_hash: [u8; 32],
     pub deposit: u64,
     pub withdraw: u64,
+    /// Slot of the last proven batch
+    pub last_slot: u64,
+    /// Timestamp of the last proven batch
+    pub last_timestamp: i64,
+    /// L1 slot of the last proof, or of the creation of the platform
+    pub last_proof_slot: u64,
+    /// Number of L1 slots without a proof after which the platform can be
+    /// frozen if a batch is overdue, at least [MIN_ESCAPE_HATCH_SLOTS]
+    pub escape_hatch_slots: u64,
+    /// Once frozen, no more batches are accepted and users withdraw their
+    /// balances in the last proven state
+    pub frozen: bool,
+}
+
+impl Platform {
+    /// Index of the queue page the next ramp tx is pushed to
+    pub fn ramp_queue_page(&self) -> u64 {
+        self.ramp_queue_tail / RAMP_QUEUE_PAGE_SIZE
+    }
+
+    /// Number of ramp txs waiting for a proof
+    pub fn pending_ramp_txs(&self) -> u64 {
+        self.ramp_queue_tail - self.ramp_queue_head
+    }
+
+    /// Queues a ramp tx in `page`, which must be the page at
+    /// [Platform::ramp_queue_page]
+    pub fn push_ramp_tx(&mut self, page: &mut RampQueuePage, ramp_tx: RampTx) -> Result<()> {
+        if self.pending_ramp_txs() >= MAX_PENDING_RAMP_TXS {
+            return Err(PlatformError::RampQueueFull.into());
+        }
+        self.start_pending()?;
+        page.ramp_txs.push(ramp_tx);
+        self.ramp_queue_tail += 1;
+        Ok(())
+    }
+
+    /// Number of forced txs waiting for a proof
+    pub fn pending_forced_txs(&self) -> u64 {
+        self.forced_txs_count - self.forced_txs_head
+    }
+
+    /// Queues a serialized transaction for a proof, extending the forced txs
+    /// hash chain, and returns its index
+    pub fn push_forced_tx(&mut self, tx: &[u8]) -> Result<u64> {
+        self.start_pending()?;
+        self.forced_txs_hash = chain_forced_tx(&self.forced_txs_hash, tx);
+        let index = self.forced_txs_count;
+        self.forced_txs_count += 1;
+        Ok(index)
+    }
+
+    fn start_pending(&mut self) -> Result<()> {
+        if self.pending_since == 0 {
+            self.pending_since = Clock::get()?.unix_timestamp;
+        }
+        Ok(())
+    }
+
+    /// Whether queued ramp or forced txs have waited for a proof past the deadline
+    pub fn is_batch_overdue(&self, now: i64) -> bool {
+        self.pending_since != 0 && now > self.pending_since + BATCH_DEADLINE
+    }
+
+    /// Whether no proof was submitted for more than `escape_hatch_slots`, so
+    /// that the platform can be frozen
+    pub fn is_escape_hatch_open(&self, slot: u64) -> bool {
+        slot > self.last_proof_slot.saturating_add(self.escape_hatch_slots)
+    }
 }
 
 #[macro_export]
@@ -1,11 +1,151 @@
+use crate::errors::PlatformError;
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
 
 pub const PROOF_SEED_PREFIX: &[u8] = b"proof:";
 
+/// Size of the chunks a proof is uploaded in, the last one being shorter
+pub const PROOF_CHUNK_SIZE: u64 = 800;
+
+/// Proof uploaded in chunks, which `prove` reads once complete and closes
 #[account]
 #[derive(Default, InitSpace)]
 pub struct Proof {
     pub bump: u8,
+    /// Size of the complete proof
+    pub size: u64,
+    /// Hash of the complete proof, `sha256(data)`
+    pub hash: [u8; 32],
+    /// Bitmap of the chunks written so far
+    #[max_len(0)]
+    pub chunks: Vec<u8>,
     #[max_len(0)]
     pub data: Vec<u8>,
 }
+
+impl Proof {
+    /// Space taken by the data and chunks bitmap of a proof of `size` bytes
+    pub fn space(size: u64) -> usize {
+        Self::chunks_len(size) + size as usize
+    }
+
+    fn chunks_len(size: u64) -> usize {
+        size.div_ceil(PROOF_CHUNK_SIZE).div_ceil(8) as usize
+    }
+
+    /// Starts the upload of a proof of `size` bytes hashing to `hash`
+    pub fn start(&mut self, size: u64, hash: [u8; 32]) {
+        self.size = size;
+        self.hash = hash;
+        self.chunks = vec![0; Self::chunks_len(size)];
+        self.data = vec![0; size as usize];
+    }
+
+    /// Writes `data` at `offset`, which must cover whole chunks not written yet
+    pub fn write(&mut self, offset: u64, data: &[u8]) -> Result<()> {
+        let end = offset
+            .checked_add(data.len() as u64)
+            .filter(|end| *end <= self.size)
+            .ok_or(PlatformError::InvalidProofWrite)?;
+        if data.is_empty()
+            || offset % PROOF_CHUNK_SIZE != 0
+            || (end % PROOF_CHUNK_SIZE != 0 && end != self.size)
+        {
+            return Err(PlatformError::InvalidProofWrite.into());
+        }
+
+        for chunk in offset / PROOF_CHUNK_SIZE..end.div_ceil(PROOF_CHUNK_SIZE) {
+            let (byte, bit) = ((chunk / 8) as usize, 1 << (chunk % 8));
+            if self.chunks[byte] & bit != 0 {
+                return Err(PlatformError::ProofWriteOverlap.into());
+            }
+            self.chunks[byte] |= bit;
+        }
+        self.data[offset as usize..end as usize].copy_from_slice(data);
+
+        Ok(())
+    }
+
+    /// Checks that every chunk was written and that the data matches the hash
+    pub fn verify(&self) -> Result<()> {
+        let chunks = self.size.div_ceil(PROOF_CHUNK_SIZE);
+        if (0..chunks).any(|chunk| self.chunks[(chunk / 8) as usize] & (1 << (chunk % 8)) == 0) {
+            return Err(PlatformError::IncompleteProof.into());
+        }
+        if hashv(&[&self.data]).to_bytes() != self.hash {
+            return Err(PlatformError::InvalidProofData.into());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Proof upload started for 2.5 chunks of data
+    fn upload() -> (Proof, Vec<u8>) {
+        let data = (0..2 * PROOF_CHUNK_SIZE + PROOF_CHUNK_SIZE / 2)
+            .map(|i| i as u8)
+            .collect::<Vec<_>>();
+        let mut proof = Proof::default();
+        proof.start(data.len() as u64, hashv(&[&data]).to_bytes());
+        (proof, data)
+    }
+
+    #[test]
+    fn test_write_chunks() {
+        let (mut proof, data) = upload();
+        let chunk = PROOF_CHUNK_SIZE as usize;
+
+        // Chunks are written in any order, several at a time
+        assert_eq!(proof.verify(), Err(PlatformError::IncompleteProof.into()));
+        proof
+            .write(2 * PROOF_CHUNK_SIZE, &data[2 * chunk..])
+            .unwrap();
+        assert_eq!(proof.verify(), Err(PlatformError::IncompleteProof.into()));
+        proof.write(0, &data[..2 * chunk]).unwrap();
+        proof.verify().unwrap();
+        assert_eq!(proof.data, data);
+    }
+
+    #[test]
+    fn test_invalid_writes() {
+        let (mut proof, data) = upload();
+        let chunk = PROOF_CHUNK_SIZE as usize;
+        let invalid_write = Err(PlatformError::InvalidProofWrite.into());
+
+        // Out of bounds
+        assert_eq!(
+            proof.write(2 * PROOF_CHUNK_SIZE, &data[..chunk]),
+            invalid_write
+        );
+        assert_eq!(proof.write(u64::MAX, &data[..1]), invalid_write);
+        // Not aligned to chunks
+        assert_eq!(proof.write(1, &data[1..chunk + 1]), invalid_write);
+        assert_eq!(proof.write(0, &data[..chunk - 1]), invalid_write);
+        assert_eq!(proof.write(0, &[]), invalid_write);
+
+        // Written chunks can't be rewritten, even along with new ones
+        proof
+            .write(PROOF_CHUNK_SIZE, &data[chunk..2 * chunk])
+            .unwrap();
+        assert_eq!(
+            proof.write(PROOF_CHUNK_SIZE, &data[chunk..2 * chunk]),
+            Err(PlatformError::ProofWriteOverlap.into())
+        );
+        assert_eq!(
+            proof.write(0, &data[..2 * chunk]),
+            Err(PlatformError::ProofWriteOverlap.into())
+        );
+    }
+
+    #[test]
+    fn test_hash_mismatch() {
+        let (mut proof, mut data) = upload();
+        data[0] ^= 1;
+        proof.write(0, &data).unwrap();
+        assert_eq!(proof.verify(), Err(PlatformError::InvalidProofData.into()));
+    }
+}
@@ -0,0 +1,100 @@
+use crate::errors::PlatformError;
+use crate::state::platform::Platform;
+use crate::utils::RampTx;
+use anchor_lang::prelude::*;
+
+pub const RAMP_QUEUE_SEED_PREFIX: &[u8] = b"ramp_queue:";
+
+/// Number of ramp txs held by a queue page
+pub const RAMP_QUEUE_PAGE_SIZE: u64 = 32;
+/// Number of ramp txs that can wait for a proof, so that the pages of a batch
+/// always fit in a transaction
+pub const MAX_PENDING_RAMP_TXS: u64 = 8 * RAMP_QUEUE_PAGE_SIZE;
+/// Smallest amount of lamports moved by a ramp tx
+pub const MIN_RAMP_AMOUNT: u64 = 1_000_000;
+/// Lamports charged for every ramp tx, which the sequencer can claim
+pub const RAMP_TX_FEE: u64 = 10_000;
+
+/// Smallest amount of tokens moved by a token ramp tx, a thousandth of a token
+/// of a mint with `decimals`, or one base unit for mints with fewer decimals
+pub fn min_token_ramp_amount(decimals: u8) -> u64 {
+    10u64.saturating_pow(u32::from(decimals.saturating_sub(3)))
+}
+
+/// Page of the ramp txs queue of a platform. The ramp tx at position `n` in
+/// the queue is stored in the page `n / RAMP_QUEUE_PAGE_SIZE`, which is
+/// closed to the prover once all of its ramp txs are proven.
+#[account]
+#[derive(Default, InitSpace)]
+pub struct RampQueuePage {
+    pub bump: u8,
+    pub index: u64,
+    #[max_len(RAMP_QUEUE_PAGE_SIZE)]
+    pub ramp_txs: Vec<RampTx>,
+}
+
+/// Consecutive pages of the ramp txs queue, loaded from account infos
+pub struct RampQueue<'info> {
+    first_page: u64,
+    pub pages: Vec<Account<'info, RampQueuePage>>,
+}
+
+impl<'info> RampQueue<'info> {
+    /// Loads the pages holding the ramp txs from position `start` to `end`,
+    /// which must be the first `accounts`. The accounts left are returned.
+    pub fn load<'a>(
+        platform: &Platform,
+        accounts: &'a [AccountInfo<'info>],
+        start: u64,
+        end: u64,
+    ) -> Result<(Self, &'a [AccountInfo<'info>])> {
+        let first_page = start / RAMP_QUEUE_PAGE_SIZE;
+        let count = if start < end {
+            ((end - 1) / RAMP_QUEUE_PAGE_SIZE - first_page + 1) as usize
+        } else {
+            0
+        };
+        if accounts.len() < count {
+            return Err(PlatformError::MissingRampQueuePage.into());
+        }
+
+        let (page_infos, accounts) = accounts.split_at(count);
+        let mut pages = Vec::with_capacity(count);
+        for (index, page_info) in (first_page..).zip(page_infos) {
+            let page = Account::<RampQueuePage>::try_from(page_info)?;
+            let page_key = Pubkey::create_program_address(
+                &[
+                    RAMP_QUEUE_SEED_PREFIX,
+                    platform.id.as_ref(),
+                    &index.to_le_bytes(),
+                    &[page.bump],
+                ],
+                &crate::ID,
+            )
+            .map_err(|_| PlatformError::InvalidRampQueuePage)?;
+            if page_key != page_info.key() {
+                return Err(PlatformError::InvalidRampQueuePage.into());
+            }
+            pages.push(page);
+        }
+
+        Ok((Self { first_page, pages }, accounts))
+    }
+
+    /// Ramp tx at `position` in the queue, if it is in the loaded pages
+    pub fn get(&self, position: u64) -> Option<&RampTx> {
+        let page = (position / RAMP_QUEUE_PAGE_SIZE).checked_sub(self.first_page)?;
+        self.pages
+            .get(page as usize)?
+            .ramp_txs
+            .get((position % RAMP_QUEUE_PAGE_SIZE) as usize)
+    }
+
+    pub fn get_mut(&mut self, position: u64) -> Option<&mut RampTx> {
+        let page = (position / RAMP_QUEUE_PAGE_SIZE).checked_sub(self.first_page)?;
+        self.pages
+            .get_mut(page as usize)?
+            .ramp_txs
+            .get_mut((position % RAMP_QUEUE_PAGE_SIZE) as usize)
+    }
+}
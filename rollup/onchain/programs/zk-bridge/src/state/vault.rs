@@ -0,0 +1,3 @@
+/// Seed of the platform token accounts holding the tokens locked in the rollup,
+/// one per mint. Their authority is the platform.
+pub const VAULT_SEED_PREFIX: &[u8] = b"vault:";
@@ -1,36 +1,225 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{hash::hashv, pubkey};
 
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, InitSpace)]
 pub struct RampTx {
     pub is_onramp: bool,
     pub user: Pubkey,
+    /// Lamports moved in or out of the rollup
+    pub amount: u64,
+    /// Tokens moved along with the lamports, if any
+    pub token: Option<TokenAmount>,
+}
+
+/// Amount of an SPL token locked in the platform vault of its mint
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq, Eq, InitSpace)]
+pub struct TokenAmount {
+    pub mint: Pubkey,
+    pub decimals: u8,
     pub amount: u64,
 }
 
 pub type ExecutionOutput = [u8; 32];
 
+/// Owner of the withdrawal receipts written to the rollup state
+pub const WITHDRAWAL_RECEIPT_OWNER: Pubkey = pubkey!("WithdrawReceipt1111111111111111111111111111");
+
+const WITHDRAWAL_RECEIPT_SEED: &[u8] = b"withdrawal:";
+
+/// Data of a withdrawal receipt account in the rollup state
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub struct WithdrawalReceipt {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub token: Option<TokenAmount>,
+    pub prior_state_root: [u8; 32],
+    pub index: u64,
+}
+
+impl WithdrawalReceipt {
+    /// Address the runner writes this receipt to. Accounts created by rollup
+    /// transactions can't be at this address, so they can't pass as receipts.
+    pub fn address(&self) -> Pubkey {
+        Pubkey::new_from_array(
+            hashv(&[
+                WITHDRAWAL_RECEIPT_SEED,
+                &self.prior_state_root,
+                &self.index.to_le_bytes(),
+            ])
+            .to_bytes(),
+        )
+    }
+}
+
+/// Proof that an account is stored in the rollup state tree, as generated by
+/// the `merkle-tree` crate
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub struct SparseMerkleProof {
+    /// Leaf at the end of the path, as its key and account hash
+    pub leaf: Option<(Pubkey, [u8; 32])>,
+    /// Siblings along the path, from the root down to the leaf
+    pub siblings: Vec<[u8; 32]>,
+}
+
+impl SparseMerkleProof {
+    /// Verifies that the account hash `value` is stored at `key` in the tree of the given `root`
+    pub fn verify_inclusion(&self, root: &[u8; 32], key: &Pubkey, value: &[u8; 32]) -> bool {
+        self.verify(root, key, Some(value))
+    }
+
+    /// Verifies the proof against `root`, `value` being the account hash stored
+    /// at `key` or none if no account is stored there
+    pub fn verify(&self, root: &[u8; 32], key: &Pubkey, value: Option<&[u8; 32]>) -> bool {
+        if self.siblings.len() > 256 {
+            return false;
+        }
+
+        let mut current = match (&self.leaf, value) {
+            (Some((leaf_key, leaf_value)), Some(value)) => {
+                if leaf_key != key || leaf_value != value {
+                    return false;
+                }
+                hashv(&[&[0], key.as_ref(), value]).to_bytes()
+            }
+            (Some((leaf_key, leaf_value)), None) => {
+                // The leaf of another key must be the only one in the subtree
+                // where `key` would have been
+                if leaf_key == key
+                    || (0..self.siblings.len()).any(|depth| bit(leaf_key, depth) != bit(key, depth))
+                {
+                    return false;
+                }
+                hashv(&[&[0], leaf_key.as_ref(), leaf_value]).to_bytes()
+            }
+            (None, Some(_)) => return false,
+            (None, None) => [0; 32],
+        };
+
+        for (depth, sibling) in self.siblings.iter().enumerate().rev() {
+            current = if bit(key, depth) {
+                hashv(&[&[1], sibling, &current])
+            } else {
+                hashv(&[&[1], &current, sibling])
+            }
+            .to_bytes();
+        }
+
+        current == *root
+    }
+}
+
+/// Bit of `key` at `depth` of the state tree, most significant bit first
+fn bit(key: &Pubkey, depth: usize) -> bool {
+    key.as_ref()[depth / 8] & (0x80 >> (depth % 8)) != 0
+}
+
+/// Proof that a transaction receipt is in the receipts tree of a batch, as
+/// generated by the `merkle-tree` crate
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub struct ReceiptProof {
+    /// Position of the transaction in the batch
+    pub index: u64,
+    /// Siblings along the path, from the leaf up to the root
+    pub siblings: Vec<[u8; 32]>,
+}
+
+impl ReceiptProof {
+    /// Verifies that the borsh serialized `TransactionReceipt` of the runner
+    /// is at `index` in the receipts tree of the given `root`
+    pub fn verify(&self, root: &[u8; 32], receipt: &[u8]) -> bool {
+        if self.siblings.len() >= 64 || self.index >> self.siblings.len() != 0 {
+            return false;
+        }
+
+        let mut index = self.index;
+        let mut current = hashv(&[&[0], receipt]).to_bytes();
+        for sibling in self.siblings.iter() {
+            current = if index & 1 == 1 {
+                hashv(&[&[1], sibling, &current])
+            } else {
+                hashv(&[&[1], &current, sibling])
+            }
+            .to_bytes();
+            index >>= 1;
+        }
+
+        current == *root
+    }
+}
+
+const MIRRORED_MINT_SEED: &[u8] = b"mirror:";
+
+/// Address of the SPL Token mint mirroring the L1 `mint` on the rollup
+pub fn mirrored_mint_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::new_from_array(hashv(&[MIRRORED_MINT_SEED, mint.as_ref()]).to_bytes())
+}
+
+/// Extends the forced txs hash chain with the serialized transaction `tx`,
+/// `sha256(previous || tx)`
+pub fn chain_forced_tx(previous: &[u8; 32], tx: &[u8]) -> [u8; 32] {
+    hashv(&[previous, tx]).to_bytes()
+}
+
+impl RampTx {
+    /// Extends the ramp txs hash chain with this tx, `sha256(previous || borsh(self))`
+    pub fn chain(&self, previous: &[u8; 32]) -> Result<[u8; 32]> {
+        Ok(hashv(&[previous, &self.try_to_vec()?]).to_bytes())
+    }
+}
+
+/// Public values of the proof of a batch, as committed by the program. They
+/// only hold commitments, the data of the batch being published separately.
 #[derive(AnchorSerialize, AnchorDeserialize, Debug)]
 pub struct CommitedValues {
-    pub input: ExecutionInput,
+    /// Root of the state the batch was executed on
+    pub prior_state_root: [u8; 32],
+    /// Number of the batch, incremented by one for every batch
+    pub batch_number: u64,
     pub output: ExecutionOutput,
+    /// Hash of the serialized transactions of the batch
+    pub txs_hash: [u8; 32],
+    /// Hash chain over the ramp txs of the batch
+    pub ramp_txs_hash: [u8; 32],
+    /// Index of the first forced tx executed by the batch
+    pub forced_txs_start: u64,
+    /// Number of forced txs executed by the batch
+    pub forced_txs_count: u64,
+    /// Hash chain over the forced txs up to the last one executed by the
+    /// batch, see [chain_forced_tx]
+    pub forced_txs_hash: [u8; 32],
+    /// Hash of whether each ramp tx was applied, see [hash_ramp_results]
+    pub ramp_results_hash: [u8; 32],
+    /// Hash of the block context of the batch, see [BlockHeader::hash]
+    pub header_hash: [u8; 32],
+    /// Root of the receipts of the transactions of the batch
+    pub receipts_root: [u8; 32],
 }
 
+/// Hash of whether each ramp tx of a batch was applied, `sha256(borsh(results))`
+pub fn hash_ramp_results(ramp_results: &[bool]) -> Result<[u8; 32]> {
+    Ok(hashv(&[&ramp_results.try_to_vec()?]).to_bytes())
+}
+
+/// Fees charged to the transactions of the batch
 #[derive(AnchorSerialize, AnchorDeserialize, Debug)]
-pub struct ExecutionInput {
-    pub rollup_accounts: RollupState, // use Vec<State> instead
-    pub txs: Vec<u8>,                 // Vec of serialized transactions: Vec<Transaction>
-    pub ramp_txs: Vec<RampTx>,
+pub struct FeeConfig {
+    pub lamports_per_signature: u64,
+    pub collector: Pubkey,
 }
 
+/// Block context the batch was executed in
 #[derive(AnchorSerialize, AnchorDeserialize, Debug)]
-pub struct RollupState {
-    pub states: Vec<State>,
+pub struct BlockHeader {
+    pub slot: u64,
+    pub unix_timestamp: i64,
+    pub epoch: u64,
 }
 
-#[derive(AnchorDeserialize, AnchorSerialize, Debug)]
-pub struct State {
-    pub pubkey: Pubkey,
-    pub account: SerializableAccount,
+impl BlockHeader {
+    /// Hash of the header and the fees of a batch, `sha256(borsh(self) || borsh(fees))`
+    pub fn hash(&self, fees: &FeeConfig) -> Result<[u8; 32]> {
+        Ok(hashv(&[&self.try_to_vec()?, &fees.try_to_vec()?]).to_bytes())
+    }
 }
 
 /// Borsh Serializable Solana Account
@@ -48,6 +237,21 @@ pub struct SerializableAccount {
     pub rent_epoch: u64,
 }
 
+impl SerializableAccount {
+    /// Hash of the account in the rollup state tree, over its bincode encoding
+    pub fn hash(&self) -> [u8; 32] {
+        hashv(&[
+            &self.lamports.to_le_bytes(),
+            &(self.data.len() as u64).to_le_bytes(),
+            &self.data,
+            self.owner.as_ref(),
+            &[self.executable as u8],
+            &self.rent_epoch.to_le_bytes(),
+        ])
+        .to_bytes()
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Debug)]
 pub struct SP1Groth16Proof {
     pub proof: Vec<u8>,
@@ -1,37 +1,91 @@
 use {
     anchor_client::{
         solana_sdk::{
-            compute_budget, hash::Hash, message::Message, native_token::LAMPORTS_PER_SOL,
-            pubkey::Pubkey, signature::read_keypair_file, signer::Signer, system_program,
-            transaction::Transaction,
+            account::Account,
+            clock::Clock,
+            compute_budget,
+            hash::{hashv, Hash},
+            instruction::{AccountMeta, Instruction, InstructionError},
+            message::Message,
+            native_token::LAMPORTS_PER_SOL,
+            pubkey::Pubkey,
+            signature::{read_keypair_file, Keypair},
+            signer::Signer,
+            system_instruction, system_program,
+            transaction::{Transaction, TransactionError, VersionedTransaction},
         },
         Client, Cluster,
     },
-    anchor_lang::AnchorDeserialize,
-    litesvm::LiteSVM,
-    std::{rc::Rc, str::FromStr},
+    anchor_lang::{
+        solana_program::{incinerator, program_option::COption, program_pack::Pack},
+        AccountDeserialize, AccountSerialize, AnchorDeserialize, InstructionData, ToAccountMetas,
+    },
+    anchor_spl::token::spl_token,
+    borsh::BorshDeserialize,
+    litesvm::{types::TransactionResult, LiteSVM},
+    merkle_tree::{list_proof, SparseMerkleTree},
+    std::rc::Rc,
+    svm_runner_lib::{runner, RunnerOutput},
+    svm_runner_types::{
+        hash_receipts, withdrawal_receipt_address, ExecutionInput, RollupState, TransactionReceipt,
+    },
     zk_bridge::{
-        accounts, instruction,
-        instructions::{AddRampTxArgs, CreatePlatformArgs, UploadProofArgs},
-        utils::SP1Groth16Proof,
+        accounts,
+        errors::PlatformError,
+        instruction,
+        instructions::{
+            AcceptSequencerArgs, AddRampTxArgs, AddTokenRampTxArgs, ClaimWithdrawalArgs,
+            CreatePlatformArgs, EscapeWithdrawalArgs, ForceTransactionArgs, ProveArgs,
+            RotateSequencerArgs, UploadProofArgs, VerifyReceiptArgs,
+        },
+        state::{
+            min_token_ramp_amount, BatchReceipts, ForcedTx, Platform, Proof, RampQueuePage,
+            BATCH_DEADLINE, BATCH_RECEIPTS_SEED_PREFIX, ESCAPE_SEED_PREFIX,
+            FORCED_TX_INCLUSION_DELAY, FORCED_TX_SEED_PREFIX, MAX_FORCED_TX_SIZE,
+            MAX_PENDING_RAMP_TXS, MIN_ESCAPE_HATCH_SLOTS, MIN_RAMP_AMOUNT, MIN_SEQUENCER_BOND,
+            NULLIFIER_SEED_PREFIX, PLATFORM_SEED_PREFIX, PROOF_CHUNK_SIZE, PROOF_SEED_PREFIX,
+            RAMP_QUEUE_PAGE_SIZE, RAMP_QUEUE_SEED_PREFIX, RAMP_SEED_PREFIX, RAMP_TX_FEE,
+            SEQUENCER_ROTATION_DELAY, SLASH_REWARD_DIVISOR, VAULT_SEED_PREFIX,
+        },
+        utils::{
+            chain_forced_tx, BlockHeader, FeeConfig, RampTx, ReceiptProof, SP1Groth16Proof,
+            SerializableAccount, SparseMerkleProof, TokenAmount,
+        },
     },
 };
 
+/// Reads a file written by the `prove` command of the zk script, which must be
+/// run again whenever the program or the proven batch change
+fn fixture(name: &str) -> Vec<u8> {
+    std::fs::read(format!("../../../zk/script/{name}"))
+        .unwrap_or_else(|_| panic!("missing {name}, run `prove` in zk/script first"))
+}
+
 #[tokio::test]
 async fn runs() -> anyhow::Result<()> {
     let payer = read_keypair_file("../../keypairSender.json").unwrap();
+    let receiver = read_keypair_file("../../keypairReceiver.json").unwrap();
     let bytes = include_bytes!("../../../target/deploy/zk_bridge.so");
-    let proof = include_bytes!("../../../../zk/script/grooth16_proof.bin");
-
-    let initial_state_hash = Hash::from_str("Aq2kL5qUSQTAPxXyMTWWm618UvyQy3axDweYzEHM9bHa")?;
+    let proof = fixture("grooth16_proof.bin");
+    let proof = proof.as_slice();
+    let batch = svm_runner_types::BatchData::try_from_slice(&fixture("batch.bin"))?;
     let program_id = zk_bridge::ID;
     let mut svm = LiteSVM::new();
 
     let grooth16_proof = SP1Groth16Proof::try_from_slice(proof)?;
     println!("grooth 16 proof: {:#?}", &grooth16_proof);
+    // The platform starts from the state the proven batch was executed on
+    let initial_state_hash = grooth16_proof.sp1_public_inputs.prior_state_root;
+
+    // The batch must not be timestamped in the future of L1
+    let mut clock = svm.get_sysvar::<Clock>();
+    clock.unix_timestamp = batch.input.header.unix_timestamp;
+    svm.set_sysvar(&clock);
 
     svm.add_program(program_id, bytes);
     svm.airdrop(&payer.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&receiver.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .unwrap();
 
     // use localnet, but we never really use it. We use lite-svm connection instead
     let provider = Client::new(Cluster::Localnet, Rc::new(&payer));
@@ -46,10 +100,30 @@ async fn runs() -> anyhow::Result<()> {
         &[b"ramp:", platform_id.as_ref(), payer.pubkey().as_ref()],
         &program_id,
     );
+    let (ramp_queue_page_key, _ramp_queue_page_bump) = Pubkey::find_program_address(
+        &[b"ramp_queue:", platform_id.as_ref(), &0u64.to_le_bytes()],
+        &program_id,
+    );
+    let (receiver_ramp_key, _receiver_ramp_bump) = Pubkey::find_program_address(
+        &[b"ramp:", platform_id.as_ref(), receiver.pubkey().as_ref()],
+        &program_id,
+    );
+    let (forced_tx_key, _forced_tx_bump) = Pubkey::find_program_address(
+        &[b"forced_tx:", platform_id.as_ref(), &0u64.to_le_bytes()],
+        &program_id,
+    );
     let (proof_key, _proof_bump) = Pubkey::find_program_address(
         &[b"proof:", platform_id.as_ref(), payer.pubkey().as_ref()],
         &program_id,
     );
+    let (batch_receipts_key, _batch_receipts_bump) = Pubkey::find_program_address(
+        &[
+            b"batch_receipts:",
+            platform_id.as_ref(),
+            &1u64.to_le_bytes(),
+        ],
+        &program_id,
+    );
 
     // Create Platform & Add Ramp transaction
     let create_platform_ix = program
@@ -62,7 +136,9 @@ async fn runs() -> anyhow::Result<()> {
         .args(instruction::CreatePlatform {
             args: CreatePlatformArgs {
                 id: platform_id,
-                initial_state_hash: initial_state_hash.to_bytes(),
+                initial_state_hash,
+                bond: LAMPORTS_PER_SOL,
+                escape_hatch_slots: 216_000,
             },
         })
         .instructions()?
@@ -72,6 +148,7 @@ async fn runs() -> anyhow::Result<()> {
         .request()
         .accounts(accounts::AddRampTx {
             platform: platform_key,
+            ramp_queue_page: ramp_queue_page_key,
             ramp: ramp_key,
             ramper: payer.pubkey(),
             system_program: system_program::ID,
@@ -85,9 +162,51 @@ async fn runs() -> anyhow::Result<()> {
         .instructions()?
         .remove(0);
 
+    // The batch also on-ramps the receiver and executes a forced transaction
+    let add_receiver_ramp_ix = program
+        .request()
+        .accounts(accounts::AddRampTx {
+            platform: platform_key,
+            ramp_queue_page: ramp_queue_page_key,
+            ramp: receiver_ramp_key,
+            ramper: receiver.pubkey(),
+            system_program: system_program::ID,
+        })
+        .args(instruction::AddRampTx {
+            args: AddRampTxArgs {
+                is_onramp: true,
+                amount: LAMPORTS_PER_SOL,
+            },
+        })
+        .instructions()?
+        .remove(0);
+    let force_transaction_ix = program
+        .request()
+        .accounts(accounts::ForceTransaction {
+            user: payer.pubkey(),
+            platform: platform_key,
+            forced_tx: forced_tx_key,
+            system_program: system_program::ID,
+        })
+        .args(instruction::ForceTransaction {
+            args: ForceTransactionArgs {
+                tx: batch.input.forced_txs[0].clone(),
+            },
+        })
+        .instructions()?
+        .remove(0);
+
     let tx = Transaction::new(
-        &[&payer],
-        Message::new(&[create_platform_ix, add_ramp_ix], Some(&payer.pubkey())),
+        &[&payer, &receiver],
+        Message::new(
+            &[
+                create_platform_ix,
+                add_ramp_ix,
+                add_receiver_ramp_ix,
+                force_transaction_ix,
+            ],
+            Some(&payer.pubkey()),
+        ),
         svm.latest_blockhash(),
     );
 
@@ -101,6 +220,7 @@ async fn runs() -> anyhow::Result<()> {
         .args(instruction::UploadProof {
             args: UploadProofArgs {
                 proof_size: proof.len() as u64,
+                proof_hash: hashv(&[proof]).to_bytes(),
                 proof_data: proof.to_vec(),
                 offset: 0,
             },
@@ -124,13 +244,31 @@ async fn runs() -> anyhow::Result<()> {
     // Prove
     let prove_tx = program
         .request()
-        .args(instruction::Prove {})
+        .args(instruction::Prove {
+            args: ProveArgs {
+                header: BlockHeader {
+                    slot: batch.input.header.slot,
+                    unix_timestamp: batch.input.header.unix_timestamp,
+                    epoch: batch.input.header.epoch,
+                },
+                fees: FeeConfig {
+                    lamports_per_signature: batch.input.fees.lamports_per_signature,
+                    collector: Pubkey::new_from_array(batch.input.fees.collector.to_bytes()),
+                },
+                ramp_results: batch.ramp_results,
+            },
+        })
         .accounts(accounts::Prove {
             platform: platform_key,
             proof: proof_key,
             prover: payer.pubkey(),
+            batch_receipts: batch_receipts_key,
             system_program: system_program::ID,
         })
+        .accounts(vec![
+            AccountMeta::new(ramp_queue_page_key, false),
+            AccountMeta::new(forced_tx_key, false),
+        ])
         .instructions()?
         .remove(0);
     let compute_budget_ix =
@@ -145,3 +283,1490 @@ async fn runs() -> anyhow::Result<()> {
 
     anyhow::Ok(())
 }
+
+/// Program address derived from `seeds`
+fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &zk_bridge::ID).0
+}
+
+fn platform_key(platform_id: &Pubkey) -> Pubkey {
+    pda(&[PLATFORM_SEED_PREFIX, platform_id.as_ref()])
+}
+
+fn bridge_ix(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: zk_bridge::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Sends the instructions paid by the first signer. The blockhash is expired
+/// afterwards, so that the same transaction can be sent again.
+fn send(
+    svm: &mut LiteSVM,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> TransactionResult {
+    let tx = Transaction::new(
+        signers,
+        Message::new(instructions, Some(&signers[0].pubkey())),
+        svm.latest_blockhash(),
+    );
+    let result = svm.send_transaction(tx);
+    svm.expire_blockhash();
+    result
+}
+
+fn assert_error(result: TransactionResult, error: PlatformError) {
+    match result.map_err(|failed| failed.err) {
+        Err(TransactionError::InstructionError(_, InstructionError::Custom(code))) => {
+            assert_eq!(code, u32::from(error), "expected {error:?}")
+        }
+        result => panic!("expected {error:?}, got {result:?}"),
+    }
+}
+
+fn read_account<T: AccountDeserialize>(svm: &LiteSVM, key: &Pubkey) -> T {
+    let account = svm.get_account(key).expect("missing account");
+    T::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+/// Overwrites the data of an existing program account
+fn write_account<T: AccountSerialize>(svm: &mut LiteSVM, key: &Pubkey, value: &T) {
+    let mut account = svm.get_account(key).expect("missing account");
+    let mut data = vec![];
+    value.try_serialize(&mut data).unwrap();
+    account.data[..data.len()].copy_from_slice(&data);
+    svm.set_account(*key, account).unwrap();
+}
+
+/// Creates an account of the bridge holding `value`
+fn create_account<T: AccountSerialize>(svm: &mut LiteSVM, key: &Pubkey, value: &T) {
+    let mut data = vec![];
+    value.try_serialize(&mut data).unwrap();
+    let account = Account {
+        lamports: svm.minimum_balance_for_rent_exemption(data.len()),
+        data,
+        owner: zk_bridge::ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+    svm.set_account(*key, account).unwrap();
+}
+
+fn lamports(svm: &LiteSVM, key: &Pubkey) -> u64 {
+    svm.get_account(key)
+        .map(|account| account.lamports)
+        .unwrap_or_default()
+}
+
+/// LiteSVM with the bridge deployed and a funded sequencer
+fn setup() -> (LiteSVM, Keypair) {
+    let mut svm = LiteSVM::new();
+    svm.add_program(
+        zk_bridge::ID,
+        include_bytes!("../../../target/deploy/zk_bridge.so"),
+    );
+    let sequencer = Keypair::new();
+    svm.airdrop(&sequencer.pubkey(), 100 * LAMPORTS_PER_SOL)
+        .unwrap();
+    (svm, sequencer)
+}
+
+/// Creates a platform sequenced by `sequencer` from `initial_state_hash`,
+/// returning its id
+fn create_platform(svm: &mut LiteSVM, sequencer: &Keypair, initial_state_hash: [u8; 32]) -> Pubkey {
+    let id = Pubkey::new_unique();
+    let ix = bridge_ix(
+        accounts::CreatePlatform {
+            platform: platform_key(&id),
+            sequencer: sequencer.pubkey(),
+            system_program: system_program::ID,
+        },
+        instruction::CreatePlatform {
+            args: CreatePlatformArgs {
+                id,
+                initial_state_hash,
+                bond: MIN_SEQUENCER_BOND,
+                escape_hatch_slots: MIN_ESCAPE_HATCH_SLOTS,
+            },
+        },
+    );
+    send(svm, &[ix], &[sequencer]).unwrap();
+    id
+}
+
+/// Changes the platform as proofs and ramp txs would, `lamports` being added
+/// to it along with the deposits
+fn update_platform(
+    svm: &mut LiteSVM,
+    platform_id: &Pubkey,
+    lamports: u64,
+    update: impl FnOnce(&mut Platform),
+) {
+    let key = platform_key(platform_id);
+    let mut platform = read_account::<Platform>(svm, &key);
+    update(&mut platform);
+    write_account(svm, &key, &platform);
+    let mut account = svm.get_account(&key).unwrap();
+    account.lamports += lamports;
+    svm.set_account(key, account).unwrap();
+}
+
+/// Executes the ramp txs on the rollup `state` in a batch without transactions
+fn execute_ramp_txs(state: RollupState, ramp_txs: Vec<svm_runner_types::RampTx>) -> RollupState {
+    execute_batch(state, &[], ramp_txs).state
+}
+
+/// Runs a batch of the rollup on `state` with the runner, at slot 1
+fn execute_batch(
+    state: RollupState,
+    txs: &[VersionedTransaction],
+    ramp_txs: Vec<svm_runner_types::RampTx>,
+) -> RunnerOutput {
+    runner(ExecutionInput {
+        accounts: state,
+        txs: bincode::serialize(txs).unwrap(),
+        ramp_txs,
+        forced_txs: vec![],
+        forced_txs_start: 0,
+        forced_txs_prior_hash: Default::default(),
+        header: svm_runner_types::BlockHeader {
+            slot: 1,
+            unix_timestamp: 0,
+            epoch: 0,
+        },
+        fees: Default::default(),
+        batch_number: 1,
+    })
+    .unwrap()
+}
+
+/// Root of the rollup `state`, as proven to the bridge
+fn state_root(state: &RollupState) -> [u8; 32] {
+    svm_runner_types::hash_state(state.clone()).to_bytes()
+}
+
+/// Account at `pubkey` in the rollup `state` and its inclusion proof
+fn state_proof(state: &RollupState, pubkey: &Pubkey) -> (SerializableAccount, SparseMerkleProof) {
+    let accounts = state
+        .states
+        .iter()
+        .map(|state| (state.pubkey, Account::from(state.account.clone())))
+        .collect::<Vec<_>>();
+    let mut tree = SparseMerkleTree::new();
+    tree.update(
+        accounts
+            .iter()
+            .map(|(pubkey, account)| (*pubkey, Some(account))),
+    );
+    let proof = tree.generate_proof(pubkey);
+
+    let account = &state
+        .states
+        .iter()
+        .find(|state| state.pubkey == *pubkey)
+        .expect("missing rollup account")
+        .account;
+    (
+        SerializableAccount {
+            lamports: account.lamports,
+            data: account.data.clone(),
+            owner: account.owner,
+            executable: account.executable,
+            rent_epoch: account.rent_epoch,
+        },
+        SparseMerkleProof {
+            leaf: proof.leaf.map(|(key, hash)| (key, hash.to_bytes())),
+            siblings: proof.siblings.iter().map(|hash| hash.to_bytes()).collect(),
+        },
+    )
+}
+
+fn system_account(lamports: u64) -> svm_runner_types::SerializableAccount {
+    svm_runner_types::SerializableAccount {
+        lamports,
+        owner: system_program::ID,
+        ..Default::default()
+    }
+}
+
+fn ramp_queue_page_key(platform_id: &Pubkey, page: u64) -> Pubkey {
+    pda(&[
+        RAMP_QUEUE_SEED_PREFIX,
+        platform_id.as_ref(),
+        &page.to_le_bytes(),
+    ])
+}
+
+fn ramp_key(platform_id: &Pubkey, user: &Pubkey) -> Pubkey {
+    pda(&[RAMP_SEED_PREFIX, platform_id.as_ref(), user.as_ref()])
+}
+
+fn vault_key(platform_id: &Pubkey, mint: &Pubkey) -> Pubkey {
+    pda(&[VAULT_SEED_PREFIX, platform_id.as_ref(), mint.as_ref()])
+}
+
+/// Creates an SPL Token mint on L1
+fn create_mint(svm: &mut LiteSVM, decimals: u8) -> Pubkey {
+    let mint = Pubkey::new_unique();
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        mint_authority: COption::Some(Pubkey::new_unique()),
+        supply: u64::MAX / 2,
+        decimals,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut data);
+    let account = Account {
+        lamports: svm.minimum_balance_for_rent_exemption(data.len()),
+        data,
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+    svm.set_account(mint, account).unwrap();
+    mint
+}
+
+/// Creates an SPL Token account of `owner` holding `amount` of `mint` on L1
+fn create_token_account(svm: &mut LiteSVM, mint: &Pubkey, owner: &Pubkey, amount: u64) -> Pubkey {
+    let token_account = Pubkey::new_unique();
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..spl_token::state::Account::default()
+    }
+    .pack_into_slice(&mut data);
+    let account = Account {
+        lamports: svm.minimum_balance_for_rent_exemption(data.len()),
+        data,
+        owner: spl_token::ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+    svm.set_account(token_account, account).unwrap();
+    token_account
+}
+
+fn token_balance(svm: &LiteSVM, token_account: &Pubkey) -> u64 {
+    let account = svm
+        .get_account(token_account)
+        .expect("missing token account");
+    spl_token::state::Account::unpack(&account.data)
+        .unwrap()
+        .amount
+}
+
+/// Moves the L1 clock `seconds` forward
+fn advance_clock(svm: &mut LiteSVM, seconds: i64) {
+    let mut clock = svm.get_sysvar::<Clock>();
+    clock.unix_timestamp += seconds;
+    svm.set_sysvar(&clock);
+}
+
+fn now(svm: &LiteSVM) -> i64 {
+    svm.get_sysvar::<Clock>().unix_timestamp
+}
+
+fn proof_key(platform_id: &Pubkey, prover: &Pubkey) -> Pubkey {
+    pda(&[PROOF_SEED_PREFIX, platform_id.as_ref(), prover.as_ref()])
+}
+
+fn batch_receipts_key(platform_id: &Pubkey, batch_number: u64) -> Pubkey {
+    pda(&[
+        BATCH_RECEIPTS_SEED_PREFIX,
+        platform_id.as_ref(),
+        &batch_number.to_le_bytes(),
+    ])
+}
+
+/// Uploads `data` at `offset` of the proof of `prover`, the complete proof
+/// being `proof`
+fn upload_proof_ix(
+    platform_id: &Pubkey,
+    prover: &Pubkey,
+    proof: &[u8],
+    offset: usize,
+    data: &[u8],
+) -> Instruction {
+    bridge_ix(
+        accounts::UploadProof {
+            prover: *prover,
+            proof: proof_key(platform_id, prover),
+            platform: platform_key(platform_id),
+            system_program: system_program::ID,
+        },
+        instruction::UploadProof {
+            args: UploadProofArgs {
+                proof_size: proof.len() as u64,
+                proof_hash: hashv(&[proof]).to_bytes(),
+                offset: offset as u64,
+                proof_data: data.to_vec(),
+            },
+        },
+    )
+}
+
+/// Proves the next batch with the uploaded proof of `prover`, passing the
+/// ramp queue pages, forced txs and ramp accounts as `remaining_accounts`
+fn prove_ix(
+    platform_id: &Pubkey,
+    prover: &Pubkey,
+    batch_number: u64,
+    args: ProveArgs,
+    remaining_accounts: &[Pubkey],
+) -> Instruction {
+    let mut ix = bridge_ix(
+        accounts::Prove {
+            prover: *prover,
+            proof: proof_key(platform_id, prover),
+            platform: platform_key(platform_id),
+            batch_receipts: batch_receipts_key(platform_id, batch_number),
+            system_program: system_program::ID,
+        },
+        instruction::Prove { args },
+    );
+    ix.accounts.extend(
+        remaining_accounts
+            .iter()
+            .map(|key| AccountMeta::new(*key, false)),
+    );
+    ix
+}
+
+fn forced_tx_key(platform_id: &Pubkey, index: u64) -> Pubkey {
+    pda(&[
+        FORCED_TX_SEED_PREFIX,
+        platform_id.as_ref(),
+        &index.to_le_bytes(),
+    ])
+}
+
+/// Platform about to prove the batch of the zk script fixtures: its ramp txs
+/// are queued after `ramp_start` others, its forced txs are forced and its
+/// proof is uploaded
+struct FixtureBatch {
+    svm: LiteSVM,
+    sequencer: Keypair,
+    platform_id: Pubkey,
+    batch: svm_runner_types::BatchData,
+}
+
+impl FixtureBatch {
+    fn new(ramp_start: u64) -> Self {
+        let (mut svm, sequencer) = setup();
+        let proof = fixture("grooth16_proof.bin");
+        let batch = svm_runner_types::BatchData::try_from_slice(&fixture("batch.bin")).unwrap();
+        let values = SP1Groth16Proof::try_from_slice(&proof)
+            .unwrap()
+            .sp1_public_inputs;
+        let platform_id = create_platform(&mut svm, &sequencer, values.prior_state_root);
+
+        // The batch must not be timestamped in the future of L1
+        let mut clock = svm.get_sysvar::<Clock>();
+        clock.unix_timestamp = batch.input.header.unix_timestamp;
+        svm.set_sysvar(&clock);
+        let now = clock.unix_timestamp;
+
+        let ramp_txs = batch
+            .input
+            .ramp_txs
+            .iter()
+            .map(|ramp_tx| RampTx {
+                is_onramp: ramp_tx.is_onramp,
+                user: ramp_tx.user,
+                amount: ramp_tx.amount,
+                token: ramp_tx.token.as_ref().map(|token| TokenAmount {
+                    mint: token.mint,
+                    decimals: token.decimals,
+                    amount: token.amount,
+                }),
+            })
+            .collect::<Vec<_>>();
+        let queue = std::iter::repeat(ramp_txs[0].clone())
+            .take(ramp_start as usize)
+            .chain(ramp_txs.iter().cloned())
+            .collect::<Vec<_>>();
+        for (index, page) in (0..).zip(queue.chunks(RAMP_QUEUE_PAGE_SIZE as usize)) {
+            let (key, bump) = Pubkey::find_program_address(
+                &[
+                    RAMP_QUEUE_SEED_PREFIX,
+                    platform_id.as_ref(),
+                    &u64::to_le_bytes(index),
+                ],
+                &zk_bridge::ID,
+            );
+            let page = RampQueuePage {
+                bump,
+                index,
+                ramp_txs: page.to_vec(),
+            };
+            create_account(&mut svm, &key, &page);
+        }
+
+        let mut forced_txs_hash = batch.input.forced_txs_prior_hash.to_bytes();
+        for (index, tx) in (batch.input.forced_txs_start..).zip(&batch.input.forced_txs) {
+            forced_txs_hash = chain_forced_tx(&forced_txs_hash, tx);
+            create_forced_tx(&mut svm, &platform_id, index, now, forced_txs_hash, tx);
+        }
+
+        let (proof_account, bump) = Pubkey::find_program_address(
+            &[
+                PROOF_SEED_PREFIX,
+                platform_id.as_ref(),
+                sequencer.pubkey().as_ref(),
+            ],
+            &zk_bridge::ID,
+        );
+        let chunks = (proof.len() as u64).div_ceil(PROOF_CHUNK_SIZE).div_ceil(8);
+        create_account(
+            &mut svm,
+            &proof_account,
+            &Proof {
+                bump,
+                size: proof.len() as u64,
+                hash: hashv(&[&proof]).to_bytes(),
+                chunks: vec![u8::MAX; chunks as usize],
+                data: proof,
+            },
+        );
+
+        let deposit = ramp_txs
+            .iter()
+            .filter(|ramp_tx| ramp_tx.is_onramp)
+            .map(|ramp_tx| ramp_tx.amount)
+            .sum::<u64>();
+        update_platform(&mut svm, &platform_id, deposit, |platform| {
+            platform.ramp_queue_head = ramp_start;
+            platform.ramp_queue_tail = ramp_start + ramp_txs.len() as u64;
+            platform.forced_txs_head = batch.input.forced_txs_start;
+            platform.forced_txs_count =
+                batch.input.forced_txs_start + batch.input.forced_txs.len() as u64;
+            platform.forced_txs_hash = forced_txs_hash;
+            platform.deposit = deposit;
+            platform.pending_since = now;
+        });
+
+        Self {
+            svm,
+            sequencer,
+            platform_id,
+            batch,
+        }
+    }
+
+    /// Queue pages holding the ramp txs of the batch
+    fn ramp_pages(&self) -> Vec<Pubkey> {
+        let platform = read_account::<Platform>(&self.svm, &platform_key(&self.platform_id));
+        let first_page = platform.ramp_queue_head / RAMP_QUEUE_PAGE_SIZE;
+        let last_page = (platform.ramp_queue_tail - 1) / RAMP_QUEUE_PAGE_SIZE;
+        (first_page..=last_page)
+            .map(|page| ramp_queue_page_key(&self.platform_id, page))
+            .collect()
+    }
+
+    /// Forced txs executed by the batch
+    fn forced_txs(&self) -> Vec<Pubkey> {
+        let start = self.batch.input.forced_txs_start;
+        (start..start + self.batch.input.forced_txs.len() as u64)
+            .map(|index| forced_tx_key(&self.platform_id, index))
+            .collect()
+    }
+
+    /// Proves the batch, passing `remaining_accounts` after the ones of
+    /// `prove`
+    fn prove(&mut self, remaining_accounts: &[Pubkey]) -> TransactionResult {
+        let header = &self.batch.input.header;
+        let args = ProveArgs {
+            header: BlockHeader {
+                slot: header.slot,
+                unix_timestamp: header.unix_timestamp,
+                epoch: header.epoch,
+            },
+            fees: FeeConfig {
+                lamports_per_signature: self.batch.input.fees.lamports_per_signature,
+                collector: self.batch.input.fees.collector,
+            },
+            ramp_results: self.batch.ramp_results.clone(),
+        };
+        let ix = prove_ix(
+            &self.platform_id,
+            &self.sequencer.pubkey(),
+            self.batch.input.batch_number,
+            args,
+            remaining_accounts,
+        );
+        let compute_budget_ix =
+            compute_budget::ComputeBudgetInstruction::set_compute_unit_limit(1_400_000);
+        send(&mut self.svm, &[compute_budget_ix, ix], &[&self.sequencer])
+    }
+}
+
+/// Creates the forced tx at `index` of the platform, forced at `timestamp`
+fn create_forced_tx(
+    svm: &mut LiteSVM,
+    platform_id: &Pubkey,
+    index: u64,
+    timestamp: i64,
+    hash: [u8; 32],
+    tx: &[u8],
+) {
+    let (key, bump) = Pubkey::find_program_address(
+        &[
+            FORCED_TX_SEED_PREFIX,
+            platform_id.as_ref(),
+            &index.to_le_bytes(),
+        ],
+        &zk_bridge::ID,
+    );
+    let forced_tx = ForcedTx {
+        bump,
+        index,
+        timestamp,
+        hash,
+        tx: tx.to_vec(),
+    };
+    create_account(svm, &key, &forced_tx);
+}
+
+#[test]
+fn claim_withdrawal_once() {
+    let (mut svm, sequencer) = setup();
+    let user = Keypair::new();
+    svm.airdrop(&user.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    // The user withdraws a SOL on the rollup, leaving a receipt in the state
+    let prior_state = RollupState {
+        states: vec![svm_runner_types::State {
+            pubkey: user.pubkey(),
+            account: system_account(2 * LAMPORTS_PER_SOL),
+        }],
+    };
+    let receipt = withdrawal_receipt_address(&svm_runner_types::hash_state(prior_state.clone()), 0);
+    let state = execute_ramp_txs(
+        prior_state,
+        vec![svm_runner_types::RampTx {
+            is_onramp: false,
+            user: user.pubkey(),
+            amount: LAMPORTS_PER_SOL,
+            token: None,
+        }],
+    );
+
+    // The batch was proven, accounting for the withdrawal
+    let platform_id = create_platform(&mut svm, &sequencer, state_root(&state));
+    update_platform(&mut svm, &platform_id, LAMPORTS_PER_SOL, |platform| {
+        platform.deposit = LAMPORTS_PER_SOL;
+        platform.withdraw = LAMPORTS_PER_SOL;
+    });
+
+    let (account, proof) = state_proof(&state, &receipt);
+    let claim = bridge_ix(
+        accounts::ClaimWithdrawal {
+            ramper: user.pubkey(),
+            platform: platform_key(&platform_id),
+            nullifier: pda(&[
+                NULLIFIER_SEED_PREFIX,
+                platform_id.as_ref(),
+                receipt.as_ref(),
+            ]),
+            system_program: system_program::ID,
+        },
+        instruction::ClaimWithdrawal {
+            args: ClaimWithdrawalArgs {
+                receipt,
+                account,
+                proof,
+            },
+        },
+    );
+    let platform_lamports = lamports(&svm, &platform_key(&platform_id));
+    send(&mut svm, &[claim.clone()], &[&user]).unwrap();
+    assert_eq!(
+        lamports(&svm, &platform_key(&platform_id)),
+        platform_lamports - LAMPORTS_PER_SOL
+    );
+
+    // The nullifier of the receipt already exists
+    assert!(send(&mut svm, &[claim], &[&user]).is_err());
+    assert_eq!(
+        lamports(&svm, &platform_key(&platform_id)),
+        platform_lamports - LAMPORTS_PER_SOL
+    );
+}
+
+#[test]
+fn token_ramp_and_claim() {
+    let (mut svm, sequencer) = setup();
+    let user = Keypair::new();
+    svm.airdrop(&user.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+    let mint = create_mint(&mut svm, 6);
+    let user_tokens = create_token_account(&mut svm, &mint, &user.pubkey(), 10_000_000);
+    let platform_id = create_platform(&mut svm, &sequencer, [0; 32]);
+    let vault = vault_key(&platform_id, &mint);
+
+    let add_token_ramp_tx = |amount: u64| {
+        bridge_ix(
+            accounts::AddTokenRampTx {
+                ramper: user.pubkey(),
+                platform: platform_key(&platform_id),
+                ramp_queue_page: ramp_queue_page_key(&platform_id, 0),
+                ramp: ramp_key(&platform_id, &user.pubkey()),
+                mint,
+                ramper_token_account: user_tokens,
+                vault,
+                token_program: spl_token::ID,
+                system_program: system_program::ID,
+            },
+            instruction::AddTokenRampTx {
+                args: AddTokenRampTxArgs {
+                    is_onramp: true,
+                    amount,
+                    lamports: LAMPORTS_PER_SOL,
+                },
+            },
+        )
+    };
+
+    // Less than a thousandth of a token can't be bridged
+    assert_error(
+        send(
+            &mut svm,
+            &[add_token_ramp_tx(min_token_ramp_amount(6) - 1)],
+            &[&user],
+        ),
+        PlatformError::RampAmountTooSmall,
+    );
+
+    // The tokens are locked in the vault and queued along with the lamports
+    send(&mut svm, &[add_token_ramp_tx(5_000_000)], &[&user]).unwrap();
+    assert_eq!(token_balance(&svm, &vault), 5_000_000);
+    assert_eq!(token_balance(&svm, &user_tokens), 5_000_000);
+    let platform = read_account::<Platform>(&svm, &platform_key(&platform_id));
+    assert_eq!(platform.deposit, LAMPORTS_PER_SOL);
+    assert_eq!(platform.fees, RAMP_TX_FEE);
+    let page = read_account::<RampQueuePage>(&svm, &ramp_queue_page_key(&platform_id, 0));
+    assert_eq!(page.ramp_txs.len(), 1);
+    assert_eq!(page.ramp_txs[0].amount, LAMPORTS_PER_SOL);
+    assert_eq!(
+        page.ramp_txs[0].token,
+        Some(TokenAmount {
+            mint,
+            decimals: 6,
+            amount: 5_000_000,
+        })
+    );
+
+    // On the rollup, the tokens are minted to the user who withdraws some
+    let token = |amount| svm_runner_types::TokenAmount {
+        mint,
+        decimals: 6,
+        amount,
+    };
+    let prior_state = RollupState { states: vec![] };
+    let receipt = withdrawal_receipt_address(&svm_runner_types::hash_state(prior_state.clone()), 1);
+    let state = execute_ramp_txs(
+        prior_state,
+        vec![
+            svm_runner_types::RampTx {
+                is_onramp: true,
+                user: user.pubkey(),
+                amount: LAMPORTS_PER_SOL,
+                token: Some(token(5_000_000)),
+            },
+            svm_runner_types::RampTx {
+                is_onramp: false,
+                user: user.pubkey(),
+                amount: 0,
+                token: Some(token(2_000_000)),
+            },
+        ],
+    );
+    update_platform(&mut svm, &platform_id, 0, |platform| {
+        platform.last_state_hash = state_root(&state);
+    });
+
+    let (account, proof) = state_proof(&state, &receipt);
+    let claim = bridge_ix(
+        accounts::ClaimTokenWithdrawal {
+            ramper: user.pubkey(),
+            platform: platform_key(&platform_id),
+            nullifier: pda(&[
+                NULLIFIER_SEED_PREFIX,
+                platform_id.as_ref(),
+                receipt.as_ref(),
+            ]),
+            mint,
+            vault,
+            ramper_token_account: user_tokens,
+            token_program: spl_token::ID,
+            system_program: system_program::ID,
+        },
+        instruction::ClaimTokenWithdrawal {
+            args: ClaimWithdrawalArgs {
+                receipt,
+                account,
+                proof,
+            },
+        },
+    );
+    send(&mut svm, &[claim.clone()], &[&user]).unwrap();
+    assert_eq!(token_balance(&svm, &vault), 3_000_000);
+    assert_eq!(token_balance(&svm, &user_tokens), 7_000_000);
+
+    // The receipt is only claimed once
+    assert!(send(&mut svm, &[claim], &[&user]).is_err());
+    assert_eq!(token_balance(&svm, &vault), 3_000_000);
+}
+
+#[test]
+fn verify_batch_receipt() {
+    let (mut svm, sequencer) = setup();
+    let platform_id = create_platform(&mut svm, &sequencer, [0; 32]);
+
+    // Transactions referencing an unknown blockhash are rejected with a receipt
+    let payer = Keypair::new();
+    let txs = (1..=3)
+        .map(|lamports| {
+            Transaction::new_signed_with_payer(
+                &[system_instruction::transfer(
+                    &payer.pubkey(),
+                    &Pubkey::new_unique(),
+                    lamports,
+                )],
+                Some(&payer.pubkey()),
+                &[&payer],
+                Hash::default(),
+            )
+            .into()
+        })
+        .collect::<Vec<VersionedTransaction>>();
+    let receipts = execute_batch(RollupState { states: vec![] }, &txs, vec![]).receipts;
+    assert_eq!(receipts.len(), 3);
+
+    // The batch was proven with the root of its receipts
+    let (batch_receipts, bump) = Pubkey::find_program_address(
+        &[
+            BATCH_RECEIPTS_SEED_PREFIX,
+            platform_id.as_ref(),
+            &1u64.to_le_bytes(),
+        ],
+        &zk_bridge::ID,
+    );
+    create_account(
+        &mut svm,
+        &batch_receipts,
+        &BatchReceipts {
+            bump,
+            batch_number: 1,
+            receipts_root: hash_receipts(&receipts).to_bytes(),
+        },
+    );
+
+    let leaves = receipts
+        .iter()
+        .map(TransactionReceipt::hash)
+        .collect::<Vec<_>>();
+    let verify = |receipt: &TransactionReceipt, index: usize| {
+        bridge_ix(
+            accounts::VerifyReceipt {
+                platform: platform_key(&platform_id),
+                batch_receipts,
+            },
+            instruction::VerifyReceipt {
+                args: VerifyReceiptArgs {
+                    receipt: borsh::to_vec(receipt).unwrap(),
+                    proof: ReceiptProof {
+                        index: index as u64,
+                        siblings: list_proof(&leaves, index)
+                            .iter()
+                            .map(|sibling| sibling.to_bytes())
+                            .collect(),
+                    },
+                },
+            },
+        )
+    };
+    for (index, receipt) in receipts.iter().enumerate() {
+        send(&mut svm, &[verify(receipt, index)], &[&sequencer]).unwrap();
+    }
+
+    // A receipt is only proven at its own position, and can't be altered
+    assert_error(
+        send(&mut svm, &[verify(&receipts[0], 1)], &[&sequencer]),
+        PlatformError::InvalidReceiptProof,
+    );
+    let forged = TransactionReceipt {
+        error: None,
+        ..receipts[2].clone()
+    };
+    assert_error(
+        send(&mut svm, &[verify(&forged, 2)], &[&sequencer]),
+        PlatformError::InvalidReceiptProof,
+    );
+}
+
+#[test]
+fn rotate_sequencer_after_delay() {
+    let (mut svm, sequencer) = setup();
+    let new_sequencer = Keypair::new();
+    svm.airdrop(&new_sequencer.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .unwrap();
+    let platform_id = create_platform(&mut svm, &sequencer, [0; 32]);
+
+    let rotate = |proposer: &Keypair| {
+        bridge_ix(
+            accounts::RotateSequencer {
+                proposer: proposer.pubkey(),
+                platform: platform_key(&platform_id),
+            },
+            instruction::RotateSequencer {
+                args: RotateSequencerArgs {
+                    new_sequencer: new_sequencer.pubkey(),
+                },
+            },
+        )
+    };
+    let accept = bridge_ix(
+        accounts::AcceptSequencer {
+            new_sequencer: new_sequencer.pubkey(),
+            previous_sequencer: sequencer.pubkey(),
+            platform: platform_key(&platform_id),
+            system_program: system_program::ID,
+        },
+        instruction::AcceptSequencer {
+            args: AcceptSequencerArgs {
+                bond: MIN_SEQUENCER_BOND,
+            },
+        },
+    );
+
+    // Only the sequencer can propose while it is bonded
+    assert_error(
+        send(&mut svm, &[rotate(&new_sequencer)], &[&new_sequencer]),
+        PlatformError::InvalidSequencer,
+    );
+    send(&mut svm, &[rotate(&sequencer)], &[&sequencer]).unwrap();
+
+    // The new sequencer waits for the rotation delay
+    assert_error(
+        send(&mut svm, &[accept.clone()], &[&new_sequencer]),
+        PlatformError::RotationNotReady,
+    );
+    advance_clock(&mut svm, SEQUENCER_ROTATION_DELAY - 1);
+    assert_error(
+        send(&mut svm, &[accept.clone()], &[&new_sequencer]),
+        PlatformError::RotationNotReady,
+    );
+    advance_clock(&mut svm, 1);
+
+    // The bond of the previous sequencer is returned
+    let sequencer_lamports = lamports(&svm, &sequencer.pubkey());
+    send(&mut svm, &[accept], &[&new_sequencer]).unwrap();
+    assert_eq!(
+        lamports(&svm, &sequencer.pubkey()),
+        sequencer_lamports + MIN_SEQUENCER_BOND
+    );
+    let platform = read_account::<Platform>(&svm, &platform_key(&platform_id));
+    assert_eq!(platform.sequencer, new_sequencer.pubkey());
+    assert_eq!(platform.bond, MIN_SEQUENCER_BOND);
+    assert_eq!(platform.pending_sequencer, None);
+}
+
+#[test]
+fn slash_overdue_sequencer() {
+    let (mut svm, sequencer) = setup();
+    let reporter = Keypair::new();
+    let payer = Keypair::new();
+    svm.airdrop(&reporter.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    svm.airdrop(&payer.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    let platform_id = create_platform(&mut svm, &sequencer, [0; 32]);
+
+    let slash = |reporter: &Keypair| {
+        bridge_ix(
+            accounts::SlashSequencer {
+                reporter: reporter.pubkey(),
+                platform: platform_key(&platform_id),
+                incinerator: incinerator::ID,
+            },
+            instruction::SlashSequencer {},
+        )
+    };
+
+    // Nothing waits for a proof
+    assert_error(
+        send(&mut svm, &[slash(&reporter)], &[&payer, &reporter]),
+        PlatformError::BatchNotOverdue,
+    );
+
+    // Ramp txs wait for a proof, but the deadline is not passed yet
+    let pending_since = now(&svm);
+    update_platform(&mut svm, &platform_id, 0, |platform| {
+        platform.pending_since = pending_since;
+    });
+    advance_clock(&mut svm, BATCH_DEADLINE);
+    assert_error(
+        send(&mut svm, &[slash(&reporter)], &[&payer, &reporter]),
+        PlatformError::BatchNotOverdue,
+    );
+    advance_clock(&mut svm, 1);
+
+    // The sequencer can't collect the reward itself
+    assert_error(
+        send(&mut svm, &[slash(&sequencer)], &[&payer, &sequencer]),
+        PlatformError::SelfReport,
+    );
+
+    // The reporter gets a share of the bond, the rest is burned
+    let reward = MIN_SEQUENCER_BOND / SLASH_REWARD_DIVISOR;
+    let platform_lamports = lamports(&svm, &platform_key(&platform_id));
+    let incinerator_lamports = lamports(&svm, &incinerator::ID);
+    send(&mut svm, &[slash(&reporter)], &[&payer, &reporter]).unwrap();
+    assert_eq!(
+        lamports(&svm, &reporter.pubkey()),
+        LAMPORTS_PER_SOL + reward
+    );
+    assert_eq!(
+        lamports(&svm, &incinerator::ID),
+        incinerator_lamports + MIN_SEQUENCER_BOND - reward
+    );
+    assert_eq!(
+        lamports(&svm, &platform_key(&platform_id)),
+        platform_lamports - MIN_SEQUENCER_BOND
+    );
+    let platform = read_account::<Platform>(&svm, &platform_key(&platform_id));
+    assert_eq!(platform.bond, 0);
+    assert_eq!(platform.pending_since, now(&svm));
+}
+
+#[test]
+fn replace_gone_sequencer() {
+    let (mut svm, sequencer) = setup();
+    let new_sequencer = Keypair::new();
+    svm.airdrop(&new_sequencer.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .unwrap();
+    let platform_id = create_platform(&mut svm, &sequencer, [0; 32]);
+
+    // The sequencer was slashed, with ramp txs still waiting for a proof
+    let pending_since = now(&svm);
+    update_platform(&mut svm, &platform_id, 0, |platform| {
+        platform.bond = 0;
+        platform.fees = RAMP_TX_FEE;
+        platform.pending_since = pending_since;
+    });
+
+    let rotate = bridge_ix(
+        accounts::RotateSequencer {
+            proposer: new_sequencer.pubkey(),
+            platform: platform_key(&platform_id),
+        },
+        instruction::RotateSequencer {
+            args: RotateSequencerArgs {
+                new_sequencer: new_sequencer.pubkey(),
+            },
+        },
+    );
+    let accept = bridge_ix(
+        accounts::AcceptSequencer {
+            new_sequencer: new_sequencer.pubkey(),
+            previous_sequencer: sequencer.pubkey(),
+            platform: platform_key(&platform_id),
+            system_program: system_program::ID,
+        },
+        instruction::AcceptSequencer {
+            args: AcceptSequencerArgs {
+                bond: MIN_SEQUENCER_BOND,
+            },
+        },
+    );
+
+    // It still has until the deadline to bond again and prove
+    assert_error(
+        send(&mut svm, &[rotate.clone()], &[&new_sequencer]),
+        PlatformError::InvalidSequencer,
+    );
+    advance_clock(&mut svm, BATCH_DEADLINE + 1);
+
+    // Anyone can then take over, without waiting for the rotation delay
+    let sequencer_lamports = lamports(&svm, &sequencer.pubkey());
+    send(&mut svm, &[rotate, accept], &[&new_sequencer]).unwrap();
+    assert_eq!(
+        lamports(&svm, &sequencer.pubkey()),
+        sequencer_lamports + RAMP_TX_FEE
+    );
+    let platform = read_account::<Platform>(&svm, &platform_key(&platform_id));
+    assert_eq!(platform.sequencer, new_sequencer.pubkey());
+    assert_eq!(platform.bond, MIN_SEQUENCER_BOND);
+    assert_eq!(platform.fees, 0);
+    // The new sequencer has until the next deadline to prove
+    assert_eq!(platform.pending_since, now(&svm));
+}
+
+#[test]
+fn prove_requires_sequencer() {
+    let (mut svm, sequencer) = setup();
+    let prover = Keypair::new();
+    svm.airdrop(&prover.pubkey(), 10 * LAMPORTS_PER_SOL)
+        .unwrap();
+    let platform_id = create_platform(&mut svm, &sequencer, [0; 32]);
+
+    let proof = vec![1; 100];
+    assert_error(
+        send(
+            &mut svm,
+            &[upload_proof_ix(
+                &platform_id,
+                &prover.pubkey(),
+                &proof,
+                0,
+                &proof,
+            )],
+            &[&prover],
+        ),
+        PlatformError::InvalidSequencer,
+    );
+
+    // Even with a complete proof, only the sequencer can prove
+    let (proof_account, bump) = Pubkey::find_program_address(
+        &[
+            PROOF_SEED_PREFIX,
+            platform_id.as_ref(),
+            prover.pubkey().as_ref(),
+        ],
+        &zk_bridge::ID,
+    );
+    create_account(
+        &mut svm,
+        &proof_account,
+        &Proof {
+            bump,
+            size: proof.len() as u64,
+            hash: hashv(&[&proof]).to_bytes(),
+            chunks: vec![1],
+            data: proof,
+        },
+    );
+    let args = ProveArgs {
+        header: BlockHeader {
+            slot: 1,
+            unix_timestamp: 0,
+            epoch: 0,
+        },
+        fees: FeeConfig {
+            lamports_per_signature: 0,
+            collector: Pubkey::default(),
+        },
+        ramp_results: vec![],
+    };
+    assert_error(
+        send(
+            &mut svm,
+            &[prove_ix(&platform_id, &prover.pubkey(), 1, args, &[])],
+            &[&prover],
+        ),
+        PlatformError::InvalidSequencer,
+    );
+}
+
+#[test]
+fn force_transaction_then_escape() {
+    let (mut svm, sequencer) = setup();
+    advance_clock(&mut svm, 1_740_787_200);
+    let user = Keypair::new();
+    svm.airdrop(&user.pubkey(), LAMPORTS_PER_SOL).unwrap();
+
+    // The user holds 2 SOL in the last proven state
+    let state = RollupState {
+        states: vec![svm_runner_types::State {
+            pubkey: user.pubkey(),
+            account: system_account(2 * LAMPORTS_PER_SOL),
+        }],
+    };
+    let platform_id = create_platform(&mut svm, &sequencer, state_root(&state));
+    update_platform(&mut svm, &platform_id, 2 * LAMPORTS_PER_SOL, |platform| {
+        platform.deposit = 2 * LAMPORTS_PER_SOL;
+    });
+
+    let (account, proof) = state_proof(&state, &user.pubkey());
+    let escape = bridge_ix(
+        accounts::EscapeWithdrawal {
+            ramper: user.pubkey(),
+            platform: platform_key(&platform_id),
+            nullifier: pda(&[
+                ESCAPE_SEED_PREFIX,
+                platform_id.as_ref(),
+                user.pubkey().as_ref(),
+            ]),
+            system_program: system_program::ID,
+        },
+        instruction::EscapeWithdrawal {
+            args: EscapeWithdrawalArgs {
+                address: user.pubkey(),
+                account: Some(account),
+                proof,
+            },
+        },
+    );
+    let force = |index: u64, tx: Vec<u8>| {
+        bridge_ix(
+            accounts::ForceTransaction {
+                user: user.pubkey(),
+                platform: platform_key(&platform_id),
+                forced_tx: forced_tx_key(&platform_id, index),
+                system_program: system_program::ID,
+            },
+            instruction::ForceTransaction {
+                args: ForceTransactionArgs { tx },
+            },
+        )
+    };
+    let freeze = bridge_ix(
+        accounts::FreezePlatform {
+            reporter: user.pubkey(),
+            platform: platform_key(&platform_id),
+        },
+        instruction::FreezePlatform {},
+    );
+
+    // Users can't leave while the platform is live
+    assert_error(
+        send(&mut svm, &[escape.clone()], &[&user]),
+        PlatformError::PlatformNotFrozen,
+    );
+
+    // The transaction is queued along with the hash chain up to it
+    let tx = vec![7; 200];
+    assert_error(
+        send(
+            &mut svm,
+            &[force(0, vec![7; MAX_FORCED_TX_SIZE + 1])],
+            &[&user],
+        ),
+        PlatformError::InvalidForcedTx,
+    );
+    send(&mut svm, &[force(0, tx.clone())], &[&user]).unwrap();
+    let forced_at = now(&svm);
+    let forced_tx = read_account::<ForcedTx>(&svm, &forced_tx_key(&platform_id, 0));
+    assert_eq!(forced_tx.index, 0);
+    assert_eq!(forced_tx.timestamp, forced_at);
+    assert_eq!(forced_tx.hash, chain_forced_tx(&[0; 32], &tx));
+    assert_eq!(forced_tx.tx, tx);
+    let platform = read_account::<Platform>(&svm, &platform_key(&platform_id));
+    assert_eq!(platform.forced_txs_count, 1);
+    assert_eq!(platform.forced_txs_hash, forced_tx.hash);
+    assert_eq!(platform.pending_since, forced_at);
+
+    // The sequencer never proves it. The platform is frozen once no proof
+    // was submitted for the escape hatch delay and the batch is overdue.
+    assert_error(
+        send(&mut svm, &[freeze.clone()], &[&user]),
+        PlatformError::EscapeHatchClosed,
+    );
+    svm.warp_to_slot(platform.last_proof_slot + MIN_ESCAPE_HATCH_SLOTS + 1);
+    assert_error(
+        send(&mut svm, &[freeze.clone()], &[&user]),
+        PlatformError::BatchNotOverdue,
+    );
+    let mut clock = svm.get_sysvar::<Clock>();
+    clock.unix_timestamp = forced_at + BATCH_DEADLINE + 1;
+    svm.set_sysvar(&clock);
+    send(&mut svm, &[freeze.clone()], &[&user]).unwrap();
+    assert!(read_account::<Platform>(&svm, &platform_key(&platform_id)).frozen);
+    assert_error(
+        send(&mut svm, &[force(1, tx)], &[&user]),
+        PlatformError::PlatformFrozen,
+    );
+
+    // The user withdraws their balance in the last proven state, once
+    let platform_lamports = lamports(&svm, &platform_key(&platform_id));
+    send(&mut svm, &[escape.clone()], &[&user]).unwrap();
+    assert_eq!(
+        lamports(&svm, &platform_key(&platform_id)),
+        platform_lamports - 2 * LAMPORTS_PER_SOL
+    );
+
+    // The nullifier of the account already exists
+    assert!(send(&mut svm, &[escape], &[&user]).is_err());
+    assert_eq!(
+        lamports(&svm, &platform_key(&platform_id)),
+        platform_lamports - 2 * LAMPORTS_PER_SOL
+    );
+}
+
+#[test]
+fn prove_checks_forced_txs_range() {
+    let mut batch = FixtureBatch::new(0);
+
+    // The batch executed the forced txs from the first one, not the ones
+    // waiting for a proof
+    let platform_id = batch.platform_id;
+    update_platform(&mut batch.svm, &platform_id, 0, |platform| {
+        platform.forced_txs_head += 1;
+        platform.forced_txs_count += 1;
+    });
+    let mut remaining_accounts = batch.ramp_pages();
+    remaining_accounts.extend(batch.forced_txs());
+    assert_error(
+        batch.prove(&remaining_accounts),
+        PlatformError::InvalidForcedTxsRange,
+    );
+}
+
+#[test]
+fn prove_checks_forced_txs_hash() {
+    let mut batch = FixtureBatch::new(0);
+
+    // The forced tx on L1 is not the one the batch executed
+    let key = batch.forced_txs()[0];
+    let mut forced_tx = read_account::<ForcedTx>(&batch.svm, &key);
+    forced_tx.hash = [1; 32];
+    write_account(&mut batch.svm, &key, &forced_tx);
+
+    let mut remaining_accounts = batch.ramp_pages();
+    remaining_accounts.extend(batch.forced_txs());
+    assert_error(
+        batch.prove(&remaining_accounts),
+        PlatformError::InvalidForcedTxsHash,
+    );
+}
+
+#[test]
+fn prove_requires_stale_forced_txs() {
+    let mut batch = FixtureBatch::new(0);
+
+    // Another transaction was forced after the ones of the batch
+    let platform_id = batch.platform_id;
+    let platform = read_account::<Platform>(&batch.svm, &platform_key(&platform_id));
+    let next_index = platform.forced_txs_count;
+    let forced_at = now(&batch.svm);
+    create_forced_tx(
+        &mut batch.svm,
+        &platform_id,
+        next_index,
+        forced_at,
+        chain_forced_tx(&platform.forced_txs_hash, &[7; 200]),
+        &[7; 200],
+    );
+    update_platform(&mut batch.svm, &platform_id, 0, |platform| {
+        platform.forced_txs_count += 1;
+    });
+    let mut remaining_accounts = batch.ramp_pages();
+    remaining_accounts.extend(batch.forced_txs());
+    remaining_accounts.push(forced_tx_key(&platform_id, next_index));
+
+    // The next forced tx is always passed, to show it's not stale
+    assert_error(
+        batch.prove(&remaining_accounts[..remaining_accounts.len() - 1]),
+        PlatformError::MissingForcedTx,
+    );
+
+    // Once it waited for the inclusion delay, the batch must execute it
+    advance_clock(&mut batch.svm, FORCED_TX_INCLUSION_DELAY);
+    assert_error(
+        batch.prove(&remaining_accounts),
+        PlatformError::ForcedTxNotExecuted,
+    );
+
+    // Until then, it is left for a later batch
+    advance_clock(&mut batch.svm, -1);
+    batch.prove(&remaining_accounts).unwrap();
+    for key in batch.forced_txs() {
+        assert!(batch.svm.get_account(&key).is_none());
+    }
+    assert!(batch
+        .svm
+        .get_account(&forced_tx_key(&platform_id, next_index))
+        .is_some());
+    let platform = read_account::<Platform>(&batch.svm, &platform_key(&platform_id));
+    assert_eq!(platform.forced_txs_head, next_index);
+    assert_eq!(platform.pending_since, now(&batch.svm));
+}
+
+#[test]
+fn ramp_queue_limits_and_fees() {
+    let (mut svm, sequencer) = setup();
+    let user = Keypair::new();
+    svm.airdrop(&user.pubkey(), 10 * LAMPORTS_PER_SOL).unwrap();
+    let platform_id = create_platform(&mut svm, &sequencer, [0; 32]);
+
+    // Pushes to `page` of the queue
+    let add_ramp_tx = |page: u64, amount: u64| {
+        bridge_ix(
+            accounts::AddRampTx {
+                ramper: user.pubkey(),
+                platform: platform_key(&platform_id),
+                ramp_queue_page: ramp_queue_page_key(&platform_id, page),
+                ramp: ramp_key(&platform_id, &user.pubkey()),
+                system_program: system_program::ID,
+            },
+            instruction::AddRampTx {
+                args: AddRampTxArgs {
+                    is_onramp: true,
+                    amount,
+                },
+            },
+        )
+    };
+    let claim_fees = |sequencer: &Keypair| {
+        bridge_ix(
+            accounts::ClaimFees {
+                sequencer: sequencer.pubkey(),
+                platform: platform_key(&platform_id),
+            },
+            instruction::ClaimFees {},
+        )
+    };
+
+    assert_error(
+        send(&mut svm, &[add_ramp_tx(0, MIN_RAMP_AMOUNT - 1)], &[&user]),
+        PlatformError::RampAmountTooSmall,
+    );
+
+    // The fee is held by the platform apart from the deposit
+    let platform_lamports = lamports(&svm, &platform_key(&platform_id));
+    send(&mut svm, &[add_ramp_tx(0, MIN_RAMP_AMOUNT)], &[&user]).unwrap();
+    assert_eq!(
+        lamports(&svm, &platform_key(&platform_id)),
+        platform_lamports + MIN_RAMP_AMOUNT + RAMP_TX_FEE
+    );
+    let platform = read_account::<Platform>(&svm, &platform_key(&platform_id));
+    assert_eq!(platform.deposit, MIN_RAMP_AMOUNT);
+    assert_eq!(platform.fees, RAMP_TX_FEE);
+
+    // Only the sequencer gets the fees
+    assert_error(
+        send(&mut svm, &[claim_fees(&user)], &[&user]),
+        PlatformError::InvalidSequencer,
+    );
+    send(&mut svm, &[claim_fees(&sequencer)], &[&sequencer]).unwrap();
+    assert_eq!(
+        lamports(&svm, &platform_key(&platform_id)),
+        platform_lamports + MIN_RAMP_AMOUNT
+    );
+    let platform = read_account::<Platform>(&svm, &platform_key(&platform_id));
+    assert_eq!(platform.fees, 0);
+
+    // The queue is full until a proof pops its ramp txs
+    update_platform(&mut svm, &platform_id, 0, |platform| {
+        platform.ramp_queue_tail = platform.ramp_queue_head + MAX_PENDING_RAMP_TXS;
+    });
+    let last_page = MAX_PENDING_RAMP_TXS / RAMP_QUEUE_PAGE_SIZE;
+    assert_error(
+        send(
+            &mut svm,
+            &[add_ramp_tx(last_page, MIN_RAMP_AMOUNT)],
+            &[&user],
+        ),
+        PlatformError::RampQueueFull,
+    );
+    update_platform(&mut svm, &platform_id, 0, |platform| {
+        platform.ramp_queue_head += 1;
+    });
+    send(
+        &mut svm,
+        &[add_ramp_tx(last_page, MIN_RAMP_AMOUNT)],
+        &[&user],
+    )
+    .unwrap();
+}
+
+#[test]
+fn prove_closes_proven_pages() {
+    // The ramp txs of the batch span the end of the first page
+    let mut batch = FixtureBatch::new(RAMP_QUEUE_PAGE_SIZE - 1);
+    let ramp_pages = batch.ramp_pages();
+    assert_eq!(ramp_pages.len(), 2);
+    let forced_txs = batch.forced_txs();
+    let remaining_accounts = [ramp_pages.clone(), forced_txs.clone()].concat();
+    batch.prove(&remaining_accounts).unwrap();
+
+    let platform = read_account::<Platform>(&batch.svm, &platform_key(&batch.platform_id));
+    assert_eq!(platform.batch_number, batch.batch.input.batch_number);
+    assert_eq!(
+        platform.ramp_queue_head,
+        RAMP_QUEUE_PAGE_SIZE - 1 + batch.batch.input.ramp_txs.len() as u64
+    );
+    assert_eq!(platform.ramp_queue_head, platform.ramp_queue_tail);
+    assert_eq!(platform.pending_since, 0);
+
+    // The first page is fully proven, unlike the second one which the next
+    // ramp txs are pushed to
+    assert!(batch.svm.get_account(&ramp_pages[0]).is_none());
+    assert!(batch.svm.get_account(&ramp_pages[1]).is_some());
+    for key in forced_txs {
+        assert!(batch.svm.get_account(&key).is_none());
+    }
+}
+
+#[test]
+fn prove_requires_complete_proof() {
+    let (mut svm, sequencer) = setup();
+    let other = Keypair::new();
+    svm.airdrop(&other.pubkey(), LAMPORTS_PER_SOL).unwrap();
+    let platform_id = create_platform(&mut svm, &sequencer, [0; 32]);
+
+    // Only the first of the chunks of the proof is uploaded
+    let proof = vec![1; 2 * PROOF_CHUNK_SIZE as usize];
+    send(
+        &mut svm,
+        &[upload_proof_ix(
+            &platform_id,
+            &sequencer.pubkey(),
+            &proof,
+            0,
+            &proof[..PROOF_CHUNK_SIZE as usize],
+        )],
+        &[&sequencer],
+    )
+    .unwrap();
+    let args = ProveArgs {
+        header: BlockHeader {
+            slot: 1,
+            unix_timestamp: 0,
+            epoch: 0,
+        },
+        fees: FeeConfig {
+            lamports_per_signature: 0,
+            collector: Pubkey::default(),
+        },
+        ramp_results: vec![],
+    };
+    assert_error(
+        send(
+            &mut svm,
+            &[prove_ix(&platform_id, &sequencer.pubkey(), 1, args, &[])],
+            &[&sequencer],
+        ),
+        PlatformError::IncompleteProof,
+    );
+
+    // The upload is discarded by the prover only, who gets its rent back
+    let proof_account = proof_key(&platform_id, &sequencer.pubkey());
+    let close_proof = |prover: &Keypair| {
+        bridge_ix(
+            accounts::CloseProof {
+                prover: prover.pubkey(),
+                proof: proof_account,
+                platform: platform_key(&platform_id),
+            },
+            instruction::CloseProof {},
+        )
+    };
+    let rent = lamports(&svm, &proof_account);
+    assert!(send(&mut svm, &[close_proof(&other)], &[&other]).is_err());
+    assert_eq!(lamports(&svm, &proof_account), rent);
+
+    let sequencer_lamports = lamports(&svm, &sequencer.pubkey());
+    send(&mut svm, &[close_proof(&sequencer)], &[&sequencer]).unwrap();
+    assert!(svm.get_account(&proof_account).is_none());
+    assert_eq!(
+        lamports(&svm, &sequencer.pubkey()),
+        sequencer_lamports + rent - 5000
+    );
+}
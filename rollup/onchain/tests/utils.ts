@@ -1,6 +1,9 @@
 import * as borsh from "borsh";
 import * as anchor from "@coral-xyz/anchor";
 
+export const RAMP_QUEUE_PAGE_SIZE = 32;
+export const PROOF_CHUNK_SIZE = 800;
+
 export type RollupAccount = {
   pubkey: anchor.web3.PublicKey;
   account: {
@@ -15,45 +18,34 @@ export type RollupAccount = {
 export type Proof = {
   proof: number[];
   publicInput: {
-    input: {
-      rollupAccounts: {
-        states: RollupAccount[];
-      };
-      txs: number[];
-      rampTxs: {
-        isOnramp: boolean;
-        user: anchor.web3.PublicKey;
-        amount: anchor.BN;
-      }[];
-    };
+    priorStateRoot: number[];
+    batchNumber: anchor.BN;
     output: number[];
+    txsHash: number[];
+    rampTxsHash: number[];
+    forcedTxsStart: anchor.BN;
+    forcedTxsCount: anchor.BN;
+    forcedTxsHash: number[];
+    rampResultsHash: number[];
+    headerHash: number[];
+    receiptsRoot: number[];
   };
 };
 
 export type ProofSchema = {
   proof: Uint8Array;
   publicInput: {
-    input: {
-      rollupAccounts: {
-        states: {
-          pubkey: Uint8Array;
-          account: {
-            lamports: BigInt;
-            data: Uint8Array;
-            owner: Uint8Array;
-            executable: boolean;
-            rentEpoch: bigint;
-          };
-        }[];
-      };
-      txs: Uint8Array;
-      rampTxs: {
-        isOnramp: boolean;
-        user: Uint8Array;
-        amount: bigint;
-      }[];
-    };
+    priorStateRoot: Uint8Array;
+    batchNumber: bigint;
     output: Uint8Array;
+    txsHash: Uint8Array;
+    rampTxsHash: Uint8Array;
+    forcedTxsStart: bigint;
+    forcedTxsCount: bigint;
+    forcedTxsHash: Uint8Array;
+    rampResultsHash: Uint8Array;
+    headerHash: Uint8Array;
+    receiptsRoot: Uint8Array;
   };
 };
 
@@ -62,47 +54,175 @@ export const proofSchema: borsh.Schema = {
     proof: { array: { type: "u8" } },
     publicInput: {
       struct: {
-        input: {
+        priorStateRoot: { array: { type: "u8", len: 32 } },
+        batchNumber: "u64",
+        output: { array: { type: "u8", len: 32 } },
+        txsHash: { array: { type: "u8", len: 32 } },
+        rampTxsHash: { array: { type: "u8", len: 32 } },
+        forcedTxsStart: "u64",
+        forcedTxsCount: "u64",
+        forcedTxsHash: { array: { type: "u8", len: 32 } },
+        rampResultsHash: { array: { type: "u8", len: 32 } },
+        headerHash: { array: { type: "u8", len: 32 } },
+        receiptsRoot: { array: { type: "u8", len: 32 } },
+      },
+    },
+  },
+};
+
+// Data of a proven batch, published alongside its proof
+export type BatchSchema = {
+  input: {
+    rollupAccounts: {
+      states: {
+        pubkey: Uint8Array;
+        account: {
+          lamports: bigint;
+          data: Uint8Array;
+          owner: Uint8Array;
+          executable: boolean;
+          rentEpoch: bigint;
+        };
+      }[];
+    };
+    txs: Uint8Array;
+    rampTxs: {
+      isOnramp: boolean;
+      user: Uint8Array;
+      amount: bigint;
+      token: {
+        mint: Uint8Array;
+        decimals: number;
+        amount: bigint;
+      } | null;
+    }[];
+    forcedTxs: Uint8Array[];
+    forcedTxsStart: bigint;
+    forcedTxsPriorHash: Uint8Array;
+    header: {
+      slot: bigint;
+      unixTimestamp: bigint;
+      epoch: bigint;
+    };
+    fees: {
+      lamportsPerSignature: bigint;
+      collector: Uint8Array;
+    };
+    batchNumber: bigint;
+  };
+  rampResults: boolean[];
+};
+
+export const batchSchema: borsh.Schema = {
+  struct: {
+    input: {
+      struct: {
+        rollupAccounts: {
           struct: {
-            rollupAccounts: {
-              // Corrected typo from "rollupAcounts"
-              struct: {
-                states: {
-                  array: {
-                    type: {
+            states: {
+              array: {
+                type: {
+                  struct: {
+                    pubkey: { array: { type: "u8", len: 32 } },
+                    account: {
                       struct: {
-                        pubkey: { array: { type: "u8", len: 32 } },
-                        account: {
-                          struct: {
-                            lamports: "u64",
-                            data: { array: { type: "u8" } },
-                            owner: { array: { type: "u8", len: 32 } },
-                            executable: "bool",
-                            rentEpoch: "u64",
-                          },
-                        },
+                        lamports: "u64",
+                        data: { array: { type: "u8" } },
+                        owner: { array: { type: "u8", len: 32 } },
+                        executable: "bool",
+                        rentEpoch: "u64",
                       },
                     },
                   },
                 },
               },
             },
-            txs: { array: { type: "u8" } },
-            rampTxs: {
-              array: {
-                type: {
-                  struct: {
-                    isOnramp: "bool",
-                    user: { array: { type: "u8", len: 32 } },
-                    amount: "u64",
+          },
+        },
+        txs: { array: { type: "u8" } },
+        rampTxs: {
+          array: {
+            type: {
+              struct: {
+                isOnramp: "bool",
+                user: { array: { type: "u8", len: 32 } },
+                amount: "u64",
+                token: {
+                  option: {
+                    struct: {
+                      mint: { array: { type: "u8", len: 32 } },
+                      decimals: "u8",
+                      amount: "u64",
+                    },
                   },
                 },
               },
             },
           },
         },
-        output: { array: { type: "u8", len: 32 } },
+        forcedTxs: { array: { type: { array: { type: "u8" } } } },
+        forcedTxsStart: "u64",
+        forcedTxsPriorHash: { array: { type: "u8", len: 32 } },
+        header: {
+          struct: {
+            slot: "u64",
+            unixTimestamp: "i64",
+            epoch: "u64",
+          },
+        },
+        fees: {
+          struct: {
+            lamportsPerSignature: "u64",
+            collector: { array: { type: "u8", len: 32 } },
+          },
+        },
+        batchNumber: "u64",
       },
     },
+    rampResults: { array: { type: "bool" } },
+  },
+};
+
+// Arguments of the `prove` instruction, taken from the published batch
+export const proveArgs = (batch: BatchSchema) => ({
+  header: {
+    slot: new anchor.BN(batch.input.header.slot.toString()),
+    unixTimestamp: new anchor.BN(batch.input.header.unixTimestamp.toString()),
+    epoch: new anchor.BN(batch.input.header.epoch.toString()),
+  },
+  fees: {
+    lamportsPerSignature: new anchor.BN(
+      batch.input.fees.lamportsPerSignature.toString()
+    ),
+    collector: new anchor.web3.PublicKey(batch.input.fees.collector),
   },
+  rampResults: batch.rampResults,
+});
+
+// Page of the ramp txs queue holding the ramp tx at `position`
+export const rampQueuePageKey = (
+  programId: anchor.web3.PublicKey,
+  platformId: anchor.web3.PublicKey,
+  position: number
+) => {
+  const index = Buffer.alloc(8);
+  index.writeBigUInt64LE(BigInt(Math.floor(position / RAMP_QUEUE_PAGE_SIZE)));
+  return anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("ramp_queue:"), platformId.toBuffer(), index],
+    programId
+  )[0];
+};
+
+// The receipts root of every proven batch is kept at its number
+export const batchReceiptsKey = (
+  programId: anchor.web3.PublicKey,
+  platformId: anchor.web3.PublicKey,
+  batchNumber: number
+) => {
+  const number = Buffer.alloc(8);
+  number.writeBigUInt64LE(BigInt(batchNumber));
+  return anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("batch_receipts:"), platformId.toBuffer(), number],
+    programId
+  )[0];
 };
@@ -4,13 +4,21 @@ import { ZkBridge } from "../target/types/zk_bridge";
 import kpSender from "../keypairSender.json";
 import kpReceiver from "../keypairReceiver.json";
 import { assert } from "chai";
+import crypto from "crypto";
 import fs from "fs";
-import { proofSchema, Proof, ProofSchema } from "./utils";
+import {
+  batchReceiptsKey,
+  batchSchema,
+  BatchSchema,
+  PROOF_CHUNK_SIZE,
+  proofSchema,
+  Proof,
+  ProofSchema,
+  proveArgs,
+  rampQueuePageKey,
+} from "./utils";
 import * as borsh from "borsh";
 
-// const initialStateHash = "EukGGeg2sN2tETkZQP4kPTQxJQU859P8j5JGNLBKSt87";
-const initialStateHash = "Aq2kL5qUSQTAPxXyMTWWm618UvyQy3axDweYzEHM9bHa";
-
 const senderKeypair = anchor.web3.Keypair.fromSecretKey(
   Uint8Array.from(Buffer.from(kpSender))
 );
@@ -20,6 +28,14 @@ const receiverKeypair = anchor.web3.Keypair.fromSecretKey(
 const proof = new Uint8Array(
   fs.readFileSync("../zk/script/grooth16_proof.bin")
 );
+// The platform starts from the state the proven batch was executed on
+const initialStateHash = (
+  borsh.deserialize(proofSchema, proof) as ProofSchema
+).publicInput.priorStateRoot;
+const batch = borsh.deserialize(
+  batchSchema,
+  fs.readFileSync("../zk/script/batch.bin")
+) as BatchSchema;
 
 describe("zk-bridge", () => {
   // Configure the client to use the local cluster.
@@ -54,7 +70,10 @@ describe("zk-bridge", () => {
     await program.methods
       .createPlatform({
         id: platformId,
-        initialStateHash: Array.from(Buffer.from(initialStateHash)),
+        initialStateHash: Array.from(initialStateHash),
+        bond: new anchor.BN(anchor.web3.LAMPORTS_PER_SOL),
+        // About a day of L1 slots
+        escapeHatchSlots: new anchor.BN(216_000),
       })
       .accountsPartial({
         sequencer: senderKeypair.publicKey,
@@ -64,6 +83,8 @@ describe("zk-bridge", () => {
       .signers([senderKeypair])
       .rpc();
 
+    // The first ramp tx of the platform goes to the first page of the queue
+    const rampQueuePage = rampQueuePageKey(program.programId, platformId, 0);
     await program.methods
       .addRampTx({
         isOnramp: true,
@@ -73,6 +94,7 @@ describe("zk-bridge", () => {
         ramper: senderKeypair.publicKey,
         ramp: rampKey,
         platform: platformKey,
+        rampQueuePage,
         systemProgram: anchor.web3.SystemProgram.programId,
       })
       .signers([senderKeypair])
@@ -109,13 +131,18 @@ describe("zk-bridge", () => {
       ],
       program.programId
     );
+    // Chunks are checked against the hash of the whole proof once uploaded
+    const proofHash = Array.from(
+      crypto.createHash("sha256").update(proof).digest()
+    );
     let dataLeft = proof.slice();
     let offset = 0;
     while (dataLeft.length > 0) {
-      const size = Math.min(dataLeft.length, 800);
+      const size = Math.min(dataLeft.length, PROOF_CHUNK_SIZE);
       await program.methods
         .uploadProof({
           proofSize: new anchor.BN(proof.length),
+          proofHash,
           offset: new anchor.BN(offset),
           proofData: Buffer.from(dataLeft.subarray(0, size)),
         })
@@ -132,12 +159,16 @@ describe("zk-bridge", () => {
     }
 
     await program.methods
-      .prove()
+      .prove(proveArgs(batch))
       .accountsPartial({
         prover: senderKeypair.publicKey,
         proof: proofKey,
         platform: platformKey,
+        batchReceipts: batchReceiptsKey(program.programId, platformId, 1),
       })
+      .remainingAccounts([
+        { pubkey: rampQueuePage, isWritable: true, isSigner: false },
+      ])
       .preInstructions([
         anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({
           units: 1_400_000,
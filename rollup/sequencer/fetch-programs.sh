@@ -0,0 +1,8 @@
+#!/usr/bin/env sh
+# Dumps the SPL programs deployed in the rollup genesis from mainnet into
+# `programs/`, the default `--genesis-programs` directory of the sequencer
+set -e
+cd "$(dirname "$0")"
+mkdir -p programs
+solana program dump -u mainnet-beta TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA programs/spl_token.so
+solana program dump -u mainnet-beta ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL programs/spl_associated_token_account.so
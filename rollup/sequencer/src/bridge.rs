@@ -0,0 +1,194 @@
+//! Reads the ramp txs and forced txs queued on the L1 bridge, which the batches
+//! must execute in the order they were queued for their proofs to be accepted
+use anchor_lang::AccountDeserialize;
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_rpc_client_api::client_error::Error as ClientError;
+use solana_sdk::{commitment_config::CommitmentConfig, hash::Hash, pubkey::Pubkey};
+use svm_runner_store::{AccountStore, StoreError};
+use svm_runner_types::{hash_forced_txs, RampTx, TokenAmount};
+use thiserror::Error;
+use zk_bridge::state::{
+    ForcedTx, Platform, RampQueuePage, FORCED_TX_SEED_PREFIX, PLATFORM_SEED_PREFIX,
+    RAMP_QUEUE_PAGE_SIZE, RAMP_QUEUE_SEED_PREFIX,
+};
+
+/// Most forced txs executed by a batch, each of them being an account of the
+/// `prove` transaction
+const MAX_FORCED_TXS_PER_BATCH: u64 = 8;
+
+#[derive(Error, Debug)]
+pub enum BridgeError {
+    #[error("RPC error: {0}")]
+    Rpc(#[from] ClientError),
+    #[error("Store error: {0}")]
+    Store(#[from] StoreError),
+    #[error("Missing bridge account {0}")]
+    MissingAccount(Pubkey),
+    #[error("Invalid bridge account {0}: {1}")]
+    InvalidAccount(Pubkey, String),
+    #[error("The bridge proved batch {0}, past the rollup head {1}")]
+    AheadOfRollup(u64, u64),
+}
+
+pub type Result<T> = std::result::Result<T, BridgeError>;
+
+/// Position in the queues of the bridge of the next ramp tx and forced tx to
+/// execute, along with the forced txs hash chain up to the next forced tx
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BridgeCursor {
+    pub ramp_position: u64,
+    pub forced_position: u64,
+    pub forced_hash: Hash,
+}
+
+impl BridgeCursor {
+    /// Cursor after the execution of `queued`
+    pub fn advance(&self, queued: &QueuedTxs) -> Self {
+        Self {
+            ramp_position: self.ramp_position + queued.ramp_txs.len() as u64,
+            forced_position: self.forced_position + queued.forced_txs.len() as u64,
+            forced_hash: hash_forced_txs(&self.forced_hash, &queued.forced_txs),
+        }
+    }
+}
+
+/// Txs of the bridge queues the next batch executes
+#[derive(Debug, Clone, Default)]
+pub struct QueuedTxs {
+    pub ramp_txs: Vec<RampTx>,
+    /// Bincode serialized `VersionedTransaction`s, as forced on L1
+    pub forced_txs: Vec<Vec<u8>>,
+}
+
+impl QueuedTxs {
+    pub fn is_empty(&self) -> bool {
+        self.ramp_txs.is_empty() && self.forced_txs.is_empty()
+    }
+}
+
+/// Platform of the bridge the rollup is proven to
+pub struct Bridge {
+    rpc: RpcClient,
+    platform_id: Pubkey,
+}
+
+impl Bridge {
+    pub fn new(url: String, platform_id: Pubkey) -> Self {
+        Self {
+            rpc: RpcClient::new_with_commitment(url, CommitmentConfig::confirmed()),
+            platform_id,
+        }
+    }
+
+    /// Cursor of the batch following the head of `store`. The forced txs
+    /// follow those of the last batch, while the ramp txs follow those of the
+    /// last proven batch and of the batches committed since.
+    pub fn resume(&self, store: &AccountStore) -> Result<BridgeCursor> {
+        let platform = self.platform()?;
+        let (height, _) = store.head();
+        if platform.batch_number > height {
+            return Err(BridgeError::AheadOfRollup(platform.batch_number, height));
+        }
+
+        let mut ramp_position = platform.ramp_queue_head;
+        for batch_number in platform.batch_number + 1..=height {
+            ramp_position += store.batch(batch_number)?.input.ramp_txs.len() as u64;
+        }
+        let (forced_position, forced_hash) = if height > 0 {
+            let input = store.batch(height)?.input;
+            (
+                input.forced_txs_start + input.forced_txs.len() as u64,
+                hash_forced_txs(&input.forced_txs_prior_hash, &input.forced_txs),
+            )
+        } else {
+            (0, Hash::default())
+        };
+
+        Ok(BridgeCursor {
+            ramp_position,
+            forced_position,
+            forced_hash,
+        })
+    }
+
+    /// Txs queued from `cursor` on. The ramp txs are taken up to the end of the
+    /// page of the first one, and the forced txs up to
+    /// [MAX_FORCED_TXS_PER_BATCH], so that `prove` fits in a transaction.
+    pub fn queued(&self, cursor: &BridgeCursor) -> Result<QueuedTxs> {
+        let platform = self.platform()?;
+
+        let page_index = cursor.ramp_position / RAMP_QUEUE_PAGE_SIZE;
+        let ramp_end = platform
+            .ramp_queue_tail
+            .min((page_index + 1) * RAMP_QUEUE_PAGE_SIZE);
+        let ramp_txs = if cursor.ramp_position < ramp_end {
+            let page_key = bridge_address(&[
+                RAMP_QUEUE_SEED_PREFIX,
+                self.platform_id.as_ref(),
+                &page_index.to_le_bytes(),
+            ]);
+            let page: RampQueuePage = self.read(&page_key)?;
+            let start = (cursor.ramp_position % RAMP_QUEUE_PAGE_SIZE) as usize;
+            let end = start + (ramp_end - cursor.ramp_position) as usize;
+            page.ramp_txs
+                .get(start..end)
+                .ok_or_else(|| {
+                    BridgeError::InvalidAccount(page_key, "missing queued ramp txs".to_string())
+                })?
+                .iter()
+                .map(|ramp_tx| RampTx {
+                    is_onramp: ramp_tx.is_onramp,
+                    user: ramp_tx.user,
+                    amount: ramp_tx.amount,
+                    token: ramp_tx.token.as_ref().map(|token| TokenAmount {
+                        mint: token.mint,
+                        decimals: token.decimals,
+                        amount: token.amount,
+                    }),
+                })
+                .collect()
+        } else {
+            vec![]
+        };
+
+        let forced_end = platform
+            .forced_txs_count
+            .min(cursor.forced_position + MAX_FORCED_TXS_PER_BATCH);
+        let forced_txs = (cursor.forced_position..forced_end)
+            .map(|index| {
+                let forced_tx: ForcedTx = self.read(&bridge_address(&[
+                    FORCED_TX_SEED_PREFIX,
+                    self.platform_id.as_ref(),
+                    &index.to_le_bytes(),
+                ]))?;
+                Ok(forced_tx.tx)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(QueuedTxs {
+            ramp_txs,
+            forced_txs,
+        })
+    }
+
+    fn platform(&self) -> Result<Platform> {
+        self.read(&bridge_address(&[
+            PLATFORM_SEED_PREFIX,
+            self.platform_id.as_ref(),
+        ]))
+    }
+
+    fn read<T: AccountDeserialize>(&self, pubkey: &Pubkey) -> Result<T> {
+        let account = self
+            .rpc
+            .get_account_with_commitment(pubkey, self.rpc.commitment())?
+            .value
+            .ok_or(BridgeError::MissingAccount(*pubkey))?;
+        T::try_deserialize(&mut account.data.as_slice())
+            .map_err(|err| BridgeError::InvalidAccount(*pubkey, err.to_string()))
+    }
+}
+
+fn bridge_address(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &zk_bridge::ID).0
+}
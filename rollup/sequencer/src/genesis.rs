@@ -0,0 +1,44 @@
+//! Initial state of the rollup
+use solana_sdk::{bpf_loader, pubkey::Pubkey, rent::Rent};
+use std::{fs, io, path::Path};
+use svm_runner_types::{RollupState, SerializableAccount, State};
+
+/// Programs deployed in every genesis, with the name of their ELF file as
+/// dumped from mainnet by `fetch-programs.sh`. Token-2022 is left out, as
+/// mirrored mints are SPL Token mints whatever the program of the L1 mint.
+const GENESIS_PROGRAMS: [(&str, Pubkey); 2] = [
+    ("spl_token.so", spl_token::ID),
+    (
+        "spl_associated_token_account.so",
+        spl_associated_token_account::ID,
+    ),
+];
+
+/// Adds the SPL Token and Associated Token Account programs found
+/// in `programs_dir` to the genesis state, unless it already holds them
+pub fn add_genesis_programs(genesis: &mut RollupState, programs_dir: &Path) -> io::Result<()> {
+    for (file, program_id) in GENESIS_PROGRAMS {
+        if genesis
+            .states
+            .iter()
+            .any(|state| state.pubkey == program_id)
+        {
+            continue;
+        }
+
+        let elf = fs::read(programs_dir.join(file))?;
+        genesis.states.push(State {
+            pubkey: program_id,
+            // Deployed with the non-upgradeable loader, as on mainnet
+            account: SerializableAccount {
+                lamports: Rent::default().minimum_balance(elf.len()),
+                data: elf,
+                owner: bpf_loader::id(),
+                executable: true,
+                rent_epoch: 0,
+            },
+        });
+    }
+
+    Ok(())
+}
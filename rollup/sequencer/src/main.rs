@@ -0,0 +1,122 @@
+//! Rollup sequencer: accepts transactions over a Solana compatible JSON-RPC
+//! API and executes them in batches with the SVM runner.
+use clap::Parser;
+use jsonrpc_core::IoHandler;
+use jsonrpc_http_server::{AccessControlAllowOrigin, DomainsValidation, ServerBuilder};
+use log::info;
+use solana_sdk::pubkey::Pubkey;
+use std::{fs::File, net::SocketAddr, path::PathBuf, sync::Arc, thread, time::Duration};
+use svm_runner_store::AccountStore;
+use svm_runner_types::{FeeConfig, RollupState};
+
+mod bridge;
+mod genesis;
+mod rpc;
+mod sequencer;
+
+use bridge::Bridge;
+use rpc::{RollupRpc, RollupRpcImpl};
+use sequencer::{BatchPolicy, Sequencer};
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// Address the JSON-RPC server listens on
+    #[clap(long, env, default_value = "127.0.0.1:8899")]
+    rpc_address: SocketAddr,
+
+    /// JSON file holding the initial `RollupState`, empty if not provided.
+    /// Only used when the ledger is created.
+    #[clap(long, env)]
+    genesis: Option<String>,
+
+    /// Directory holding the ELF files of the SPL programs deployed in the
+    /// genesis, `spl_token.so` and `spl_associated_token_account.so`, as
+    /// written by `fetch-programs.sh`
+    #[clap(long, env, default_value = "programs")]
+    genesis_programs: PathBuf,
+
+    /// Directory the committed batches and state snapshots are stored in
+    #[clap(long, env, default_value = "ledger")]
+    ledger: PathBuf,
+
+    /// Number of batches between two full snapshots of the state
+    #[clap(long, env, default_value_t = 100)]
+    snapshot_interval: u64,
+
+    /// Base fee charged per signature, in lamports
+    #[clap(long, env, default_value_t = 5000)]
+    lamports_per_signature: u64,
+
+    /// Account credited with the fees of every batch
+    #[clap(long, env)]
+    fee_collector: Pubkey,
+
+    /// JSON-RPC URL of the L1 cluster the bridge is deployed on
+    #[clap(long, env)]
+    l1_rpc_url: String,
+
+    /// Id of the bridge platform the rollup is proven to, whose queued ramp
+    /// txs and forced txs every batch executes
+    #[clap(long, env)]
+    platform_id: Pubkey,
+
+    /// Maximum number of transactions in a batch
+    #[clap(long, env, default_value_t = 64)]
+    batch_size: usize,
+
+    /// Maximum time in milliseconds a transaction waits for its batch
+    #[clap(long, env, default_value_t = 1000)]
+    batch_time_ms: u64,
+}
+
+fn main() {
+    env_logger::init();
+    let args = Args::parse();
+
+    let mut genesis = match args.genesis {
+        Some(path) => {
+            let file = File::open(&path).expect("failed to open genesis file");
+            serde_json::from_reader(file).expect("failed to parse genesis file")
+        }
+        None => RollupState { states: vec![] },
+    };
+    if !args.ledger.exists() {
+        genesis::add_genesis_programs(&mut genesis, &args.genesis_programs)
+            .expect("failed to read genesis programs");
+    }
+
+    let store = AccountStore::open(&args.ledger, genesis, args.snapshot_interval)
+        .expect("failed to open ledger");
+    let sequencer = Arc::new(
+        Sequencer::new(
+            store,
+            Bridge::new(args.l1_rpc_url, args.platform_id),
+            BatchPolicy {
+                max_transactions: args.batch_size,
+                max_duration: Duration::from_millis(args.batch_time_ms),
+            },
+            FeeConfig {
+                lamports_per_signature: args.lamports_per_signature,
+                collector: args.fee_collector,
+            },
+        )
+        .expect("failed to resume the rollup"),
+    );
+
+    let batcher = sequencer.clone();
+    thread::spawn(move || batcher.run_batches());
+
+    let mut io = IoHandler::new();
+    io.extend_with(RollupRpcImpl { sequencer }.to_delegate());
+
+    let server = ServerBuilder::new(io)
+        .cors(DomainsValidation::AllowOnly(vec![
+            AccessControlAllowOrigin::Any,
+        ]))
+        .start_http(&args.rpc_address)
+        .expect("failed to start JSON-RPC server");
+
+    info!("JSON-RPC server listening on {}", args.rpc_address);
+    server.wait();
+}
@@ -0,0 +1,309 @@
+//! Subset of the Solana JSON-RPC API served by the sequencer
+use crate::sequencer::Sequencer;
+use base64::{prelude::BASE64_STANDARD, Engine};
+use jsonrpc_core::{Error, Result};
+use jsonrpc_derive::rpc;
+use solana_account_decoder_client_types::{UiAccount, UiAccountData, UiAccountEncoding};
+use solana_rpc_client_api::{
+    config::{
+        RpcAccountInfoConfig, RpcContextConfig, RpcSendTransactionConfig, RpcSignatureStatusConfig,
+        RpcSimulateTransactionConfig,
+    },
+    custom_error::RpcCustomError,
+    response::{
+        Response as RpcResponse, RpcBlockhash, RpcResponseContext, RpcSimulateTransactionResult,
+    },
+};
+use solana_sdk::{
+    pubkey::Pubkey, signature::Signature, transaction::VersionedTransaction,
+    transaction_context::TransactionReturnData,
+};
+use solana_transaction_status_client_types::{
+    TransactionConfirmationStatus, TransactionStatus, UiCompiledInstruction, UiInnerInstructions,
+    UiInstruction, UiTransactionEncoding,
+};
+use std::{str::FromStr, sync::Arc};
+use svm_runner_types::{InnerInstruction, SerializableAccount};
+
+/// Number of blocks a blockhash is reported valid for
+const MAX_PROCESSING_AGE: u64 = 150;
+
+#[rpc(server)]
+pub trait RollupRpc {
+    #[rpc(name = "getAccountInfo")]
+    fn get_account_info(
+        &self,
+        pubkey: String,
+        config: Option<RpcAccountInfoConfig>,
+    ) -> Result<RpcResponse<Option<UiAccount>>>;
+
+    #[rpc(name = "getBalance")]
+    fn get_balance(
+        &self,
+        pubkey: String,
+        config: Option<RpcContextConfig>,
+    ) -> Result<RpcResponse<u64>>;
+
+    #[rpc(name = "getLatestBlockhash")]
+    fn get_latest_blockhash(
+        &self,
+        config: Option<RpcContextConfig>,
+    ) -> Result<RpcResponse<RpcBlockhash>>;
+
+    #[rpc(name = "getSignatureStatuses")]
+    fn get_signature_statuses(
+        &self,
+        signatures: Vec<String>,
+        config: Option<RpcSignatureStatusConfig>,
+    ) -> Result<RpcResponse<Vec<Option<TransactionStatus>>>>;
+
+    #[rpc(name = "sendTransaction")]
+    fn send_transaction(
+        &self,
+        data: String,
+        config: Option<RpcSendTransactionConfig>,
+    ) -> Result<String>;
+
+    #[rpc(name = "simulateTransaction")]
+    fn simulate_transaction(
+        &self,
+        data: String,
+        config: Option<RpcSimulateTransactionConfig>,
+    ) -> Result<RpcResponse<RpcSimulateTransactionResult>>;
+}
+
+pub struct RollupRpcImpl {
+    pub sequencer: Arc<Sequencer>,
+}
+
+impl RollupRpcImpl {
+    fn context(&self) -> RpcResponseContext {
+        RpcResponseContext::new(self.sequencer.head().slot)
+    }
+}
+
+impl RollupRpc for RollupRpcImpl {
+    fn get_account_info(
+        &self,
+        pubkey: String,
+        config: Option<RpcAccountInfoConfig>,
+    ) -> Result<RpcResponse<Option<UiAccount>>> {
+        let pubkey = parse_pubkey(&pubkey)?;
+        let encoding = config
+            .and_then(|config| config.encoding)
+            .unwrap_or(UiAccountEncoding::Binary);
+
+        Ok(RpcResponse {
+            context: self.context(),
+            value: self
+                .sequencer
+                .get_account(&pubkey)
+                .map(|account| encode_account(account, encoding))
+                .transpose()?,
+        })
+    }
+
+    fn get_balance(
+        &self,
+        pubkey: String,
+        _config: Option<RpcContextConfig>,
+    ) -> Result<RpcResponse<u64>> {
+        let pubkey = parse_pubkey(&pubkey)?;
+
+        Ok(RpcResponse {
+            context: self.context(),
+            value: self
+                .sequencer
+                .get_account(&pubkey)
+                .map(|account| account.lamports)
+                .unwrap_or_default(),
+        })
+    }
+
+    fn get_latest_blockhash(
+        &self,
+        _config: Option<RpcContextConfig>,
+    ) -> Result<RpcResponse<RpcBlockhash>> {
+        let head = self.sequencer.head();
+
+        Ok(RpcResponse {
+            context: RpcResponseContext::new(head.slot),
+            value: RpcBlockhash {
+                blockhash: head.blockhash.to_string(),
+                last_valid_block_height: head.slot + MAX_PROCESSING_AGE,
+            },
+        })
+    }
+
+    fn get_signature_statuses(
+        &self,
+        signatures: Vec<String>,
+        _config: Option<RpcSignatureStatusConfig>,
+    ) -> Result<RpcResponse<Vec<Option<TransactionStatus>>>> {
+        let head = self.sequencer.head();
+        let statuses = signatures
+            .iter()
+            .map(|signature| {
+                let signature = Signature::from_str(signature)
+                    .map_err(|err| Error::invalid_params(format!("Invalid signature: {err}")))?;
+
+                Ok(self
+                    .sequencer
+                    .get_status(&signature)
+                    .map(|status| TransactionStatus {
+                        slot: status.slot,
+                        confirmations: Some(head.slot.saturating_sub(status.slot) as usize),
+                        err: status.result.clone().err(),
+                        status: status.result,
+                        confirmation_status: Some(TransactionConfirmationStatus::Confirmed),
+                    }))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(RpcResponse {
+            context: RpcResponseContext::new(head.slot),
+            value: statuses,
+        })
+    }
+
+    fn send_transaction(
+        &self,
+        data: String,
+        config: Option<RpcSendTransactionConfig>,
+    ) -> Result<String> {
+        let encoding = config.and_then(|config| config.encoding);
+        let tx = decode_transaction(&data, encoding)?;
+
+        // Malformed or badly signed transactions are rejected up front, as
+        // by the Solana preflight, instead of failing later in the batch
+        tx.sanitize()
+            .map_err(|err| Error::invalid_params(format!("Invalid transaction: {err}")))?;
+        if tx.verify_with_results().iter().any(|verified| !verified) {
+            return Err(RpcCustomError::TransactionSignatureVerificationFailure.into());
+        }
+
+        Ok(self.sequencer.queue_transaction(tx).to_string())
+    }
+
+    fn simulate_transaction(
+        &self,
+        data: String,
+        config: Option<RpcSimulateTransactionConfig>,
+    ) -> Result<RpcResponse<RpcSimulateTransactionResult>> {
+        let config = config.unwrap_or_default();
+        let tx = decode_transaction(&data, config.encoding)?;
+        let context = self.context();
+        let receipt = self
+            .sequencer
+            .simulate_transaction(tx)
+            .map_err(|err| Error {
+                message: err.to_string(),
+                ..Error::internal_error()
+            })?;
+
+        Ok(RpcResponse {
+            context,
+            value: RpcSimulateTransactionResult {
+                err: receipt
+                    .error
+                    .map(|err| bincode::deserialize(&err).expect("serialized by the runner")),
+                logs: Some(receipt.log_messages),
+                accounts: None,
+                units_consumed: Some(receipt.compute_units_consumed),
+                return_data: receipt.return_data.map(|return_data| {
+                    TransactionReturnData {
+                        program_id: return_data.program_id,
+                        data: return_data.data,
+                    }
+                    .into()
+                }),
+                inner_instructions: config
+                    .inner_instructions
+                    .then(|| encode_inner_instructions(receipt.inner_instructions)),
+                replacement_blockhash: None,
+            },
+        })
+    }
+}
+
+/// Inner instructions in the format of the Solana RPC, which leaves out the
+/// instructions that invoked none
+fn encode_inner_instructions(
+    inner_instructions: Vec<Vec<InnerInstruction>>,
+) -> Vec<UiInnerInstructions> {
+    inner_instructions
+        .into_iter()
+        .enumerate()
+        .filter(|(_, instructions)| !instructions.is_empty())
+        .map(|(index, instructions)| UiInnerInstructions {
+            index: index as u8,
+            instructions: instructions
+                .into_iter()
+                .map(|instruction| {
+                    UiInstruction::Compiled(UiCompiledInstruction {
+                        program_id_index: instruction.program_id_index,
+                        accounts: instruction.accounts,
+                        data: bs58::encode(instruction.data).into_string(),
+                        stack_height: Some(u32::from(instruction.stack_height)),
+                    })
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+fn parse_pubkey(pubkey: &str) -> Result<Pubkey> {
+    Pubkey::from_str(pubkey).map_err(|err| Error::invalid_params(format!("Invalid pubkey: {err}")))
+}
+
+/// Decodes a wire transaction, base58 unless specified otherwise as for Solana
+fn decode_transaction(
+    data: &str,
+    encoding: Option<UiTransactionEncoding>,
+) -> Result<VersionedTransaction> {
+    let bytes = match encoding.unwrap_or(UiTransactionEncoding::Base58) {
+        UiTransactionEncoding::Base58 | UiTransactionEncoding::Binary => bs58::decode(data)
+            .into_vec()
+            .map_err(|err| Error::invalid_params(format!("Invalid base58: {err}")))?,
+        UiTransactionEncoding::Base64 => BASE64_STANDARD
+            .decode(data)
+            .map_err(|err| Error::invalid_params(format!("Invalid base64: {err}")))?,
+        encoding => {
+            return Err(Error::invalid_params(format!(
+                "Unsupported encoding: {encoding:?}, use base58 or base64"
+            )))
+        }
+    };
+
+    bincode::deserialize(&bytes)
+        .map_err(|err| Error::invalid_params(format!("Invalid transaction: {err}")))
+}
+
+fn encode_account(account: SerializableAccount, encoding: UiAccountEncoding) -> Result<UiAccount> {
+    let space = account.data.len() as u64;
+    let data = match encoding {
+        UiAccountEncoding::Binary => {
+            UiAccountData::LegacyBinary(bs58::encode(&account.data).into_string())
+        }
+        UiAccountEncoding::Base58 => {
+            UiAccountData::Binary(bs58::encode(&account.data).into_string(), encoding)
+        }
+        UiAccountEncoding::Base64 => {
+            UiAccountData::Binary(BASE64_STANDARD.encode(&account.data), encoding)
+        }
+        encoding => {
+            return Err(Error::invalid_params(format!(
+                "Unsupported encoding: {encoding:?}, use base58 or base64"
+            )))
+        }
+    };
+
+    Ok(UiAccount {
+        lamports: account.lamports,
+        data,
+        owner: account.owner.to_string(),
+        executable: account.executable,
+        rent_epoch: account.rent_epoch,
+        space: Some(space),
+    })
+}
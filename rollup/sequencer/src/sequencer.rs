@@ -0,0 +1,307 @@
+//! Queues transactions and executes them in batches with the SVM runner
+use crate::bridge::{Bridge, BridgeCursor, BridgeError};
+use log::{error, info};
+use solana_sdk::{
+    clock::{Slot, UnixTimestamp},
+    epoch_schedule::EpochSchedule,
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::{self, VersionedTransaction},
+};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    sync::{Arc, Condvar, Mutex, RwLock},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use svm_runner_lib::{runner_with_accounts, RunnerError};
+use svm_runner_store::{AccountStore, StoreError};
+use svm_runner_types::{
+    BlockHeader, ExecutionInput, FeeConfig, RollupState, RollupStatus, SerializableAccount,
+    TransactionReceipt, MAX_RECENT_BLOCKHASHES, ROLLUP_STATUS_ADDRESS,
+};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SequencerError {
+    #[error("Store error: {0}")]
+    Store(#[from] StoreError),
+    #[error("Runner error: {0}")]
+    Runner(#[from] RunnerError),
+    #[error("Bridge error: {0}")]
+    Bridge(#[from] BridgeError),
+}
+
+pub type Result<T> = std::result::Result<T, SequencerError>;
+
+/// When to close the current batch
+#[derive(Debug, Clone)]
+pub struct BatchPolicy {
+    /// A batch is closed as soon as it holds this many transactions
+    pub max_transactions: usize,
+    /// A batch is closed at the latest after this long, if it holds
+    /// transactions or the bridge queued some
+    pub max_duration: Duration,
+}
+
+/// Outcome of a transaction included in a batch
+#[derive(Debug, Clone)]
+pub struct ProcessedStatus {
+    pub slot: Slot,
+    pub result: transaction::Result<()>,
+}
+
+/// Head of the rollup: the slot of the last batch and the blockhash it produced
+#[derive(Debug, Clone, Copy)]
+pub struct Head {
+    pub slot: Slot,
+    pub blockhash: Hash,
+    pub state_root: Hash,
+    pub unix_timestamp: UnixTimestamp,
+    /// Position of the next batch in the queues of the bridge
+    pub bridge_cursor: BridgeCursor,
+}
+
+pub struct Sequencer {
+    policy: BatchPolicy,
+    fees: FeeConfig,
+    /// Queues the ramp txs and forced txs executed by the batches
+    bridge: Bridge,
+    /// Rollup state after the last batch and the batches leading to it,
+    /// which batches are executed against
+    store: Arc<AccountStore>,
+    head: RwLock<Head>,
+    /// Transactions waiting for the next batch
+    queue: Mutex<Vec<VersionedTransaction>>,
+    queue_signal: Condvar,
+    /// Statuses of the transactions of the last `MAX_RECENT_BLOCKHASHES` batches
+    statuses: RwLock<HashMap<Signature, ProcessedStatus>>,
+}
+
+impl Sequencer {
+    /// Resumes the rollup from the last batch committed to `store`, and the
+    /// queues of the `bridge` from the txs that batch executed
+    pub fn new(
+        store: AccountStore,
+        bridge: Bridge,
+        policy: BatchPolicy,
+        fees: FeeConfig,
+    ) -> Result<Self> {
+        // The blockhash of the last batch is kept in the rollup status
+        let (slot, state_root) = store.head();
+        let status = RollupStatus::from_account(
+            store.get_account(&ROLLUP_STATUS_ADDRESS).as_ref(),
+            &state_root,
+        )
+        .map_err(RunnerError::InvalidRollupStatus)?;
+        let unix_timestamp = if slot > 0 {
+            store
+                .batch(slot)
+                .map(|batch| batch.input.header.unix_timestamp)
+                .unwrap_or_default()
+        } else {
+            0
+        };
+        let head = Head {
+            slot,
+            blockhash: status.latest_blockhash(),
+            state_root,
+            unix_timestamp,
+            bridge_cursor: bridge.resume(&store)?,
+        };
+
+        Ok(Self {
+            policy,
+            fees,
+            bridge,
+            store: Arc::new(store),
+            head: RwLock::new(head),
+            queue: Mutex::default(),
+            queue_signal: Condvar::new(),
+            statuses: RwLock::default(),
+        })
+    }
+
+    pub fn head(&self) -> Head {
+        *self.head.read().unwrap()
+    }
+
+    pub fn get_account(&self, pubkey: &Pubkey) -> Option<SerializableAccount> {
+        self.store.get_account(pubkey)
+    }
+
+    pub fn get_status(&self, signature: &Signature) -> Option<ProcessedStatus> {
+        self.statuses.read().unwrap().get(signature).cloned()
+    }
+
+    /// Queues a transaction for the next batch, returning its signature
+    pub fn queue_transaction(&self, tx: VersionedTransaction) -> Signature {
+        let signature = tx.signatures.first().copied().unwrap_or_default();
+
+        let mut queue = self.queue.lock().unwrap();
+        queue.push(tx);
+        if queue.len() >= self.policy.max_transactions {
+            self.queue_signal.notify_one();
+        }
+
+        signature
+    }
+
+    /// Executes a transaction against the current state without committing
+    /// it, returning its receipt
+    pub fn simulate_transaction(&self, tx: VersionedTransaction) -> Result<TransactionReceipt> {
+        let input = ExecutionInput {
+            accounts: RollupState { states: vec![] },
+            txs: bincode::serialize(&vec![tx]).unwrap(),
+            ramp_txs: vec![],
+            forced_txs: vec![],
+            forced_txs_start: 0,
+            forced_txs_prior_hash: Hash::default(),
+            header: self.next_header(),
+            fees: self.fees.clone(),
+            // Batches are proven in the order they are committed to the store
+            batch_number: self.store.head().0 + 1,
+        };
+
+        Ok(
+            runner_with_accounts(self.store.clone(), self.head().state_root, &input)?
+                .receipts
+                .pop()
+                .expect("one receipt per transaction"),
+        )
+    }
+
+    /// Waits for the batch policy to be met and executes the queued
+    /// transactions, forever
+    pub fn run_batches(self: Arc<Self>) {
+        loop {
+            let txs = {
+                let deadline = Instant::now() + self.policy.max_duration;
+                let mut queue = self.queue.lock().unwrap();
+                loop {
+                    let now = Instant::now();
+                    if queue.len() >= self.policy.max_transactions || now >= deadline {
+                        break;
+                    }
+                    let timeout = deadline
+                        .checked_duration_since(now)
+                        .unwrap_or(self.policy.max_duration);
+                    queue = self.queue_signal.wait_timeout(queue, timeout).unwrap().0;
+                }
+                let batch_len = queue.len().min(self.policy.max_transactions);
+                queue.drain(..batch_len).collect::<Vec<_>>()
+            };
+
+            if let Err(err) = self.execute_batch(&txs) {
+                error!("Failed to execute batch: {err}");
+                // Without the txs queued on the bridge the batch would not be
+                // provable, so the transactions wait for the bridge to be read
+                if let SequencerError::Bridge(_) = err {
+                    self.queue.lock().unwrap().splice(0..0, txs);
+                }
+            }
+        }
+    }
+
+    /// Executes the transactions in a new batch, after the ramp txs and forced
+    /// txs queued on the bridge, and commits the resulting state. No batch is
+    /// executed if there is nothing to execute.
+    pub fn execute_batch(&self, txs: &[VersionedTransaction]) -> Result<Option<Head>> {
+        let cursor = self.head().bridge_cursor;
+        let queued = self.bridge.queued(&cursor)?;
+        if txs.is_empty() && queued.is_empty() {
+            return Ok(None);
+        }
+
+        // The forced txs are executed first, those that don't deserialize
+        // failing without a signature
+        let signatures = queued
+            .forced_txs
+            .iter()
+            .map(|tx| {
+                bincode::deserialize::<VersionedTransaction>(tx)
+                    .ok()
+                    .and_then(|tx| tx.signatures.first().copied())
+                    .unwrap_or_default()
+            })
+            .chain(
+                txs.iter()
+                    .map(|tx| tx.signatures.first().copied().unwrap_or_default()),
+            )
+            .collect::<Vec<_>>();
+
+        // The accounts are read from the store, only the written ones being copied
+        let input = ExecutionInput {
+            accounts: RollupState { states: vec![] },
+            txs: bincode::serialize(txs).unwrap(),
+            ramp_txs: queued.ramp_txs.clone(),
+            forced_txs: queued.forced_txs.clone(),
+            forced_txs_start: cursor.forced_position,
+            forced_txs_prior_hash: cursor.forced_hash,
+            header: self.next_header(),
+            fees: self.fees.clone(),
+            // Batches are proven in the order they are committed to the store
+            batch_number: self.store.head().0 + 1,
+        };
+        let unix_timestamp = input.header.unix_timestamp;
+        let output = runner_with_accounts(self.store.clone(), self.head().state_root, &input)?;
+
+        let mut head = self.head.write().unwrap();
+        head.slot = self.store.commit_batch(input, output.updates)?;
+        let state_root = self.store.head().1;
+        head.blockhash = output.blockhash;
+        head.state_root = state_root;
+        head.unix_timestamp = unix_timestamp;
+        head.bridge_cursor = cursor.advance(&queued);
+
+        // A resubmitted transaction is rejected as already processed, which
+        // must not hide its first result
+        let mut statuses = self.statuses.write().unwrap();
+        for (signature, result) in signatures.into_iter().zip(output.transaction_results) {
+            let status = ProcessedStatus {
+                slot: head.slot,
+                result,
+            };
+            match statuses.entry(signature) {
+                Entry::Occupied(mut entry) if status.result.is_ok() => {
+                    entry.insert(status);
+                }
+                Entry::Occupied(_) => {}
+                Entry::Vacant(entry) => {
+                    entry.insert(status);
+                }
+            }
+        }
+        // Statuses are kept as long as the rollup status remembers the
+        // signatures, after which the transactions can't be processed anymore
+        statuses.retain(|_, status| status.slot + MAX_RECENT_BLOCKHASHES as Slot > head.slot);
+
+        info!(
+            "Batch {} executed {} ramp txs, {} forced txs and {} transactions, state root {}",
+            head.slot,
+            queued.ramp_txs.len(),
+            queued.forced_txs.len(),
+            txs.len(),
+            state_root
+        );
+
+        Ok(Some(*head))
+    }
+
+    /// Header of the batch following the head
+    fn next_header(&self) -> BlockHeader {
+        let head = self.head();
+        let slot = head.slot + 1;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs() as UnixTimestamp)
+            .unwrap_or_default();
+
+        BlockHeader {
+            slot,
+            // Batch timestamps never go back, even if the system clock does
+            unix_timestamp: now.max(head.unix_timestamp),
+            epoch: EpochSchedule::without_warmup().get_epoch(slot),
+        }
+    }
+}
@@ -1,21 +1,54 @@
 //! SVM runner executing transactions on the given accounts
 //!
-use itertools::Itertools;
 use solana_sdk::{
-    account::{ReadableAccount, WritableAccount},
-    transaction::Transaction,
+    account::{AccountSharedData, ReadableAccount, WritableAccount},
+    account_utils::StateMut,
+    address_lookup_table::{self, error::AddressLookupError, state::AddressLookupTable},
+    clock::Slot,
+    hash::Hash,
+    message::{
+        v0::{LoadedAddresses, MessageAddressTableLookup},
+        AddressLoader, SanitizedMessage,
+    },
+    nonce::{
+        state::{DurableNonce, State as NonceState, Versions as NonceVersions},
+        NONCED_TX_MARKER_IX_INDEX,
+    },
+    nonce_account,
+    pubkey::Pubkey,
+    reserved_account_keys::ReservedAccountKeys,
+    sanitize::Sanitize,
+    slot_hashes::SlotHashes,
+    system_program,
+    transaction::{self, AddressLoaderError, MessageHash, TransactionError, VersionedTransaction},
 };
 use solana_svm::transaction_processor::ExecutionRecordingConfig;
-use svm_runner_types::{ExecutionInput, RollupState, State};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use svm_runner_types::{
+    hash_state, next_blockhash, withdrawal_receipt_address, ExecutionInput, InnerInstruction,
+    RampTx, ReturnData, RollupState, RollupStatus, SerializableAccount, State, TransactionReceipt,
+    WithdrawalReceipt, ROLLUP_STATUS_ADDRESS, WITHDRAWAL_RECEIPT_OWNER,
+};
+use thiserror::Error;
+#[cfg(test)]
 mod data;
 mod mock_bank;
+mod tokens;
+pub use mock_bank::AccountsBase;
 use {
     crate::mock_bank::{
-        create_executable_environment, register_builtins, MockBankCallback, MockForkGraph,
+        create_executable_environment, register_builtins, rollup_feature_set, AccountsBase,
+        MockBankCallback, MockForkGraph,
     },
-    solana_sdk::transaction::SanitizedTransaction,
+    solana_sdk::{fee::FeeStructure, transaction::SanitizedTransaction},
     solana_svm::{
         account_loader::CheckedTransactionDetails,
+        nonce_info::NonceInfo,
+        transaction_processing_callback::TransactionProcessingCallback,
+        transaction_processing_result::{
+            ProcessedTransaction, TransactionProcessingResult,
+            TransactionProcessingResultExtensions,
+        },
         transaction_processor::{
             TransactionBatchProcessor, TransactionProcessingConfig,
             TransactionProcessingEnvironment,
@@ -24,71 +57,238 @@ use {
     solana_type_overrides::sync::{Arc, RwLock},
 };
 
-// const DEPLOYMENT_SLOT: u64 = 0;
-// const LAST_BLOCKHASH: Hash = Hash::new_from_array([7; 32]); // Arbitrary constant hash for advancing nonce
-const EXECUTION_SLOT: u64 = 5; // The execution slot must be greater than the deployment slot
-const EXECUTION_EPOCH: u64 = 2; // The execution epoch must be greater than the deployment epoch
-const LAMPORTS_PER_SIGNATURE: u64 = 20;
+/// Input that can't be executed, which no state can result from
+#[derive(Debug, Error)]
+pub enum RunnerError {
+    #[error("Invalid rollup status account: {0}")]
+    InvalidRollupStatus(std::io::Error),
+    #[error("Invalid transactions: {0}")]
+    InvalidTransactions(bincode::Error),
+}
+
+/// State resulting from the execution of an [ExecutionInput]
+#[derive(Debug, Clone)]
+pub struct RunnerOutput {
+    /// Root of the input state
+    pub prior_state_root: Hash,
+    pub state: RollupState,
+    /// Whether each ramp tx was applied, in the order of the input
+    pub ramp_results: Vec<bool>,
+    /// Result of each transaction, the forced txs first, in the order of the input
+    pub transaction_results: Vec<transaction::Result<()>>,
+    /// Receipt of each transaction, in the same order as `transaction_results`
+    pub receipts: Vec<TransactionReceipt>,
+    /// Blockhash produced by the batch, referenced by the next transactions
+    pub blockhash: Hash,
+}
+
+/// Accounts written by the execution of an [ExecutionInput] with
+/// [runner_with_accounts]
+#[derive(Debug, Clone)]
+pub struct BatchOutput {
+    /// Accounts written by the batch, sorted by pubkey, none if they were removed
+    pub updates: Vec<(Pubkey, Option<SerializableAccount>)>,
+    pub ramp_results: Vec<bool>,
+    pub transaction_results: Vec<transaction::Result<()>>,
+    pub receipts: Vec<TransactionReceipt>,
+    pub blockhash: Hash,
+}
+
+/// Executes the batch and returns the complete resulting rollup state: the
+/// input accounts merged with every account written by the batch, sorted by
+/// pubkey. Accounts left with zero lamports are dropped, as on Solana.
+///
+/// Ramp txs are applied before the transactions, and whether each of them
+/// succeeded is returned alongside the state. Every applied off-ramp leaves a
+/// [WithdrawalReceipt] in the state, from which it is claimed on L1. Forced
+/// txs are then executed, before `input.txs`.
+///
+/// Fees paid by the transactions are credited to the fee collector, so that
+/// the lamports in the rollup only change with ramp txs.
+///
+/// Transactions must reference one of the recent blockhashes of the
+/// [RollupStatus], or a durable nonce, and must not have been processed
+/// before, or they are rejected with `BlockhashNotFound` or `AlreadyProcessed`.
+/// The status is updated with the processed signatures and the blockhash of
+/// the batch.
+///
+/// A [TransactionReceipt] is returned for every transaction, including the
+/// ones rejected before execution.
+///
+/// `input.txs` holds bincode serialized [VersionedTransaction]s, the addresses
+/// of v0 messages being loaded from the lookup tables in the state. The batch
+/// is rejected if they don't deserialize, or if the status account of the
+/// state doesn't, as no valid sequencer produces such an input.
+pub fn runner(input: ExecutionInput) -> Result<RunnerOutput, RunnerError> {
+    let prior_state_root = hash_state(input.accounts.clone());
+    let mock_bank = MockBankCallback {
+        feature_set: Arc::new(rollup_feature_set()),
+        ..MockBankCallback::default()
+    };
 
-pub fn runner(input: ExecutionInput) -> RollupState {
-    let mock_bank = MockBankCallback::default();
     // Insert accounts in the bank
-    // for (pk, account) in &input.accounts.0 {
     for state in &input.accounts.states {
         mock_bank
             .account_shared_data
             .write()
             .unwrap()
             .insert(state.pubkey, state.account.clone().into());
-        // .insert(*pk, account.clone().into());
     }
 
-    // Process ramp txs
-    for tx in &input.ramp_txs {
-        let mut writer = mock_bank.account_shared_data.write().unwrap();
-
-        let account = writer.get_mut(&tx.user).unwrap();
+    let output = execute_batch(&mock_bank, prior_state_root, &input)?;
 
-        if tx.is_onramp {
-            account.set_lamports(account.lamports() + tx.amount);
-        } else {
-            account.set_lamports(account.lamports() - tx.amount);
-        }
+    let mut accounts = input
+        .accounts
+        .states
+        .into_iter()
+        .map(|state| (state.pubkey, state.account))
+        .collect::<BTreeMap<_, _>>();
+    for (pubkey, account) in output.updates {
+        match account {
+            Some(account) => accounts.insert(pubkey, account),
+            None => accounts.remove(&pubkey),
+        };
     }
+    let state = RollupState {
+        states: accounts
+            .into_iter()
+            .filter(|(pubkey, account)| is_kept(pubkey, account.lamports, &account.owner))
+            .map(|(pubkey, account)| State { pubkey, account })
+            .collect(),
+    };
 
-    let mut txs = vec![];
-    let mut txscheck = vec![];
+    Ok(RunnerOutput {
+        prior_state_root,
+        state,
+        ramp_results: output.ramp_results,
+        transaction_results: output.transaction_results,
+        receipts: output.receipts,
+        blockhash: output.blockhash,
+    })
+}
 
-    let transactions = bincode::deserialize::<Vec<Transaction>>(&input.txs).unwrap();
+/// Executes the batch as [runner] does, reading the accounts from `accounts`
+/// instead of `input.accounts`, which is ignored. Only the accounts written by
+/// the batch are loaded and copied, so that the state doesn't have to be.
+///
+/// `prior_state_root` must be the root of the state held by `accounts`.
+pub fn runner_with_accounts(
+    accounts: AccountsBase,
+    prior_state_root: Hash,
+    input: &ExecutionInput,
+) -> Result<BatchOutput, RunnerError> {
+    let mock_bank = MockBankCallback {
+        feature_set: Arc::new(rollup_feature_set()),
+        base: Some(accounts),
+        ..MockBankCallback::default()
+    };
+    execute_batch(&mock_bank, prior_state_root, input)
+}
 
-    // for tx in input.txs {
-    for tx in transactions {
-        let sanitized_transaction = SanitizedTransaction::from_transaction_for_tests(tx);
-        let transaction_check = Ok(CheckedTransactionDetails {
-            nonce: None,
-            lamports_per_signature: LAMPORTS_PER_SIGNATURE,
-        });
-        txs.push(sanitized_transaction);
-        txscheck.push(transaction_check);
-    }
+/// Executes the batch on the accounts of the bank, returning the accounts it
+/// wrote
+fn execute_batch(
+    mock_bank: &MockBankCallback,
+    prior_state_root: Hash,
+    input: &ExecutionInput,
+) -> Result<BatchOutput, RunnerError> {
+    let mut status = RollupStatus::from_account(
+        mock_bank
+            .get_account_shared_data(&ROLLUP_STATUS_ADDRESS)
+            .map(SerializableAccount::from)
+            .as_ref(),
+        &prior_state_root,
+    )
+    .map_err(RunnerError::InvalidRollupStatus)?;
+    let sequenced_txs = bincode::deserialize::<Vec<VersionedTransaction>>(&input.txs)
+        .map_err(RunnerError::InvalidTransactions)?;
+    // Only these accounts were written by the batch, the sysvars and builtins
+    // inserted in the bank by the environment are left out of the output
+    let mut state_keys = BTreeSet::<Pubkey>::new();
+
+    // Process ramp txs
+    let ramp_results = input
+        .ramp_txs
+        .iter()
+        .enumerate()
+        .map(|(index, tx)| {
+            let receipt = withdrawal_receipt_address(&prior_state_root, index as u64);
+            let mut ramp_keys = vec![tx.user, receipt];
+            if let Some(token) = &tx.token {
+                ramp_keys.extend(tokens::token_accounts(&tx.user, token));
+            }
+            mock_bank.load_accounts(ramp_keys.iter().copied());
+            state_keys.extend(ramp_keys);
+            apply_ramp_tx(
+                mock_bank,
+                tx,
+                receipt,
+                WithdrawalReceipt {
+                    user: tx.user,
+                    amount: tx.amount,
+                    token: tx.token.clone(),
+                    prior_state_root,
+                    index: index as u64,
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+
+    // Forced txs come from anyone on L1, so they may not even deserialize
+    let txs = input
+        .forced_txs
+        .iter()
+        .map(|tx| {
+            bincode::deserialize::<VersionedTransaction>(tx)
+                .map_err(|_| TransactionError::SanitizeFailure)
+        })
+        .chain(sequenced_txs.into_iter().map(Ok))
+        .collect::<Vec<_>>();
+    // Taken before sanitizing, so that rejected transactions get a receipt too
+    let signatures = txs
+        .iter()
+        .map(|tx| {
+            tx.as_ref()
+                .ok()
+                .and_then(|tx| tx.signatures.first())
+                .and_then(|signature| <[u8; 64]>::try_from(signature.as_ref()).ok())
+                .unwrap_or([0; 64])
+        })
+        .collect::<Vec<_>>();
 
     let batch_processor = TransactionBatchProcessor::<MockForkGraph>::new_uninitialized(
-        EXECUTION_SLOT,
-        EXECUTION_EPOCH,
+        input.header.slot,
+        input.header.epoch,
     );
 
     let fork_graph = Arc::new(RwLock::new(MockForkGraph {}));
 
     create_executable_environment(
         fork_graph.clone(),
-        &mock_bank,
+        mock_bank,
         &mut batch_processor.program_cache.write().unwrap(),
+        &input.header,
+        &status,
+        input.fees.lamports_per_signature,
     );
 
     // The sysvars must be put in the cache
-    batch_processor.fill_missing_sysvar_cache_entries(&mock_bank);
-    register_builtins(&mock_bank, &batch_processor);
+    batch_processor.fill_missing_sysvar_cache_entries(mock_bank);
+    register_builtins(mock_bank, &batch_processor);
 
+    let fee_structure = FeeStructure {
+        lamports_per_signature: input.fees.lamports_per_signature,
+        lamports_per_write_lock: 0,
+        ..FeeStructure::default()
+    };
+    let environment = TransactionProcessingEnvironment {
+        // Durable nonces are advanced to the latest blockhash
+        blockhash: status.latest_blockhash(),
+        feature_set: mock_bank.feature_set.clone(),
+        fee_structure: Some(&fee_structure),
+        lamports_per_signature: input.fees.lamports_per_signature,
+        ..TransactionProcessingEnvironment::default()
+    };
     let config = TransactionProcessingConfig {
         recording_config: ExecutionRecordingConfig {
             enable_cpi_recording: true,
@@ -98,33 +298,1003 @@ pub fn runner(input: ExecutionInput) -> RollupState {
         ..TransactionProcessingConfig::default()
     };
 
-    let result = batch_processor.load_and_execute_sanitized_transactions(
-        &mock_bank,
-        &txs,
-        txscheck,
-        &TransactionProcessingEnvironment::default(),
-        &config,
+    let mut reserved_account_keys = ReservedAccountKeys::default();
+    reserved_account_keys.update_active_set(&environment.feature_set);
+    let slot_hashes = SlotHashes::new(&status.blockhashes);
+
+    // Transactions are executed one at a time and their post-accounts committed
+    // to the bank, so that each transaction sees the writes of the previous ones,
+    // including the lookup tables it loads addresses from
+    let mut processing_results = Vec::with_capacity(txs.len());
+    for tx in txs {
+        let address_loader = StateAddressLoader {
+            mock_bank,
+            slot: input.header.slot,
+            slot_hashes: &slot_hashes,
+        };
+        let tx = match tx
+            .and_then(|tx| sanitize_transaction(tx, address_loader, &reserved_account_keys))
+        {
+            Ok(tx) => tx,
+            Err(err) => {
+                processing_results.push(Err(err));
+                continue;
+            }
+        };
+
+        let recent_blockhash = *tx.message().recent_blockhash();
+        let signature = <[u8; 64]>::try_from(tx.signature().as_ref()).unwrap();
+        let transaction_check =
+            check_transaction_age(mock_bank, &status, &tx, input.fees.lamports_per_signature)
+                .and_then(|details| {
+                    if status.is_processed(&signature) {
+                        Err(TransactionError::AlreadyProcessed)
+                    } else {
+                        Ok(details)
+                    }
+                });
+
+        let result = batch_processor.load_and_execute_sanitized_transactions(
+            mock_bank,
+            std::slice::from_ref(&tx),
+            vec![transaction_check],
+            &environment,
+            &config,
+        );
+
+        for processing_result in result.processing_results {
+            if let Ok(ProcessedTransaction::Executed(executed_tx)) = &processing_result {
+                if executed_tx.was_successful() {
+                    // Programs deployed, upgraded or closed by the transaction
+                    // must be seen by the next ones, which use a fresh tx batch cache
+                    batch_processor
+                        .program_cache
+                        .write()
+                        .unwrap()
+                        .merge(&executed_tx.programs_modified_by_tx);
+                }
+            }
+            if processing_result.is_ok() {
+                status.record(recent_blockhash, signature);
+            }
+            state_keys.extend(mock_bank.commit_transaction(&tx, &processing_result));
+            processing_results.push(processing_result);
+        }
+    }
+
+    let collected_fees = processing_results
+        .iter()
+        .filter_map(|result| result.as_ref().ok())
+        .map(|processed_tx| processed_tx.fee_details().total_fee())
+        .sum::<u64>();
+    if collected_fees > 0 {
+        mock_bank.load_accounts([input.fees.collector]);
+        let mut accounts = mock_bank.account_shared_data.write().unwrap();
+        let collector = accounts
+            .entry(input.fees.collector)
+            .or_insert_with(|| AccountSharedData::new(0, 0, &system_program::id()));
+        collector.set_lamports(collector.lamports().saturating_add(collected_fees));
+        state_keys.insert(input.fees.collector);
+    }
+
+    let blockhash = next_blockhash(
+        &status.latest_blockhash(),
+        &prior_state_root,
+        input.header.slot,
     );
+    status.advance(input.header.slot, blockhash);
+    {
+        // Lamports sent to the status account by transactions stay in it
+        mock_bank.load_accounts([ROLLUP_STATUS_ADDRESS]);
+        let mut accounts = mock_bank.account_shared_data.write().unwrap();
+        let lamports = accounts
+            .get(&ROLLUP_STATUS_ADDRESS)
+            .map(|account| account.lamports())
+            .unwrap_or_default();
+        accounts.insert(ROLLUP_STATUS_ADDRESS, status.to_account(lamports).into());
+        state_keys.insert(ROLLUP_STATUS_ADDRESS);
+    }
 
-    println!("Batch Result {:#?}", result.processing_results);
-    let post_accounts = result
-        .processing_results
+    let updates = state_keys
         .into_iter()
-        .filter_map_ok(|res| {
-            res.executed_transaction()
-                .map(|tx| tx.loaded_transaction.accounts.clone())
+        .map(|pubkey| {
+            let account = mock_bank
+                .get_account_shared_data(&pubkey)
+                .filter(|account| is_kept(&pubkey, account.lamports(), account.owner()))
+                .map(SerializableAccount::from);
+            (pubkey, account)
         })
-        .flatten()
-        .flatten()
-        .collect::<Vec<_>>();
+        .collect();
 
-    RollupState {
-        states: post_accounts
+    Ok(BatchOutput {
+        updates,
+        ramp_results,
+        transaction_results: processing_results
             .iter()
-            .map(|(pk, account)| State {
-                pubkey: *pk,
-                account: account.clone().into(),
-            })
+            .map(|result| result.flattened_result())
             .collect(),
+        receipts: signatures
+            .into_iter()
+            .zip(&processing_results)
+            .map(|(signature, result)| transaction_receipt(signature, result))
+            .collect(),
+        blockhash,
+    })
+}
+
+/// Whether the account is part of the state. Accounts without lamports are
+/// dropped, except the status account and token receipts.
+fn is_kept(pubkey: &Pubkey, lamports: u64, owner: &Pubkey) -> bool {
+    lamports != 0 || *pubkey == ROLLUP_STATUS_ADDRESS || *owner == WITHDRAWAL_RECEIPT_OWNER
+}
+
+/// Receipt of a transaction from its processing result. Transactions that
+/// were not executed have no logs and consumed no compute units.
+fn transaction_receipt(
+    signature: [u8; 64],
+    result: &TransactionProcessingResult,
+) -> TransactionReceipt {
+    let details = result
+        .as_ref()
+        .ok()
+        .and_then(|processed_tx| processed_tx.execution_details());
+
+    TransactionReceipt {
+        signature,
+        error: result
+            .flattened_result()
+            .err()
+            .map(|err| bincode::serialize(&err).unwrap()),
+        fee: result
+            .as_ref()
+            .map(|processed_tx| processed_tx.fee_details().total_fee())
+            .unwrap_or_default(),
+        compute_units_consumed: details
+            .map(|details| details.executed_units)
+            .unwrap_or_default(),
+        log_messages: details
+            .and_then(|details| details.log_messages.clone())
+            .unwrap_or_default(),
+        return_data: details
+            .and_then(|details| details.return_data.as_ref())
+            .map(|return_data| ReturnData {
+                program_id: return_data.program_id,
+                data: return_data.data.clone(),
+            }),
+        inner_instructions: details
+            .and_then(|details| details.inner_instructions.as_ref())
+            .map(|inner_instructions| {
+                inner_instructions
+                    .iter()
+                    .map(|instructions| {
+                        instructions
+                            .iter()
+                            .map(|inner| InnerInstruction {
+                                program_id_index: inner.instruction.program_id_index,
+                                accounts: inner.instruction.accounts.clone(),
+                                data: inner.instruction.data.clone(),
+                                stack_height: inner.stack_height,
+                            })
+                            .collect()
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+    }
+}
+
+/// Checks that the transaction references a recent blockhash of the rollup, or
+/// else a durable nonce, as `Bank::check_transaction_age` does on Solana
+fn check_transaction_age(
+    mock_bank: &MockBankCallback,
+    status: &RollupStatus,
+    tx: &SanitizedTransaction,
+    lamports_per_signature: u64,
+) -> transaction::Result<CheckedTransactionDetails> {
+    if status.is_blockhash_valid(tx.message().recent_blockhash()) {
+        return Ok(CheckedTransactionDetails {
+            nonce: None,
+            lamports_per_signature,
+        });
+    }
+
+    load_and_advance_nonce_account(mock_bank, status, tx.message(), lamports_per_signature)
+        .map(|nonce| CheckedTransactionDetails {
+            nonce: Some(nonce),
+            lamports_per_signature,
+        })
+        .ok_or(TransactionError::BlockhashNotFound)
+}
+
+/// Loads the nonce account of a durable nonce transaction and advances it to
+/// the latest blockhash. The nonce must be the recent blockhash of the message
+/// and its authority a signer of the `AdvanceNonceAccount` instruction.
+///
+/// The advanced account is committed even if the transaction fails, so that
+/// it can't be replayed. The fee of the batch is recorded in it, rather than
+/// the one of the batch that last advanced it.
+fn load_and_advance_nonce_account(
+    mock_bank: &MockBankCallback,
+    status: &RollupStatus,
+    message: &SanitizedMessage,
+    lamports_per_signature: u64,
+) -> Option<NonceInfo> {
+    let next_durable_nonce = DurableNonce::from_blockhash(&status.latest_blockhash());
+    // The nonce was already advanced in this batch
+    if message.recent_blockhash() == next_durable_nonce.as_hash() {
+        return None;
+    }
+
+    let nonce_address = message.get_durable_nonce()?;
+    let mut nonce_account = mock_bank.get_account_shared_data(nonce_address)?;
+    let nonce_data =
+        nonce_account::verify_nonce_account(&nonce_account, message.recent_blockhash())?;
+    if !message
+        .get_ix_signers(NONCED_TX_MARKER_IX_INDEX as usize)
+        .any(|signer| signer == &nonce_data.authority)
+    {
+        return None;
+    }
+
+    let next_nonce_state = NonceState::new_initialized(
+        &nonce_data.authority,
+        next_durable_nonce,
+        lamports_per_signature,
+    );
+    nonce_account
+        .set_state(&NonceVersions::new(next_nonce_state))
+        .ok()?;
+    Some(NonceInfo::new(*nonce_address, nonce_account))
+}
+
+/// Credits or debits the user of a ramp tx, returning whether it was applied.
+/// On-ramps create the user account if needed, off-ramps are only applied to
+/// system accounts holding enough lamports and move them to a new withdrawal
+/// receipt at `receipt`.
+///
+/// Bridged tokens are minted to or burned from the associated token account of
+/// the user for the mirrored mint, whose rent is paid by the lamports of the
+/// on-ramp. The tokens of an on-ramp that can't be applied are refunded with a
+/// receipt holding no lamports, as the lamports are refunded on L1.
+fn apply_ramp_tx(
+    mock_bank: &MockBankCallback,
+    tx: &RampTx,
+    receipt: Pubkey,
+    mut receipt_data: WithdrawalReceipt,
+) -> bool {
+    let mut accounts = mock_bank.account_shared_data.write().unwrap();
+
+    if tx.is_onramp {
+        let applied = apply_onramp(&mut accounts, tx);
+        if !applied && tx.token.is_some() {
+            receipt_data.amount = 0;
+            insert_receipt(&mut accounts, receipt, &receipt_data);
+        }
+        return applied;
+    }
+
+    // Lamports are only withdrawn from system accounts
+    let withdrawn_lamports = tx.amount > 0 || tx.token.is_none();
+    if withdrawn_lamports
+        && !accounts.get(&tx.user).is_some_and(|account| {
+            system_program::check_id(account.owner()) && account.lamports() >= tx.amount
+        })
+    {
+        return false;
+    }
+    if let Some(token) = &tx.token {
+        if !tokens::burn_mirrored(&mut accounts, &tx.user, token) {
+            return false;
+        }
+    }
+    if withdrawn_lamports {
+        let account = accounts.get_mut(&tx.user).unwrap();
+        account.set_lamports(account.lamports() - tx.amount);
+    }
+
+    insert_receipt(&mut accounts, receipt, &receipt_data);
+    true
+}
+
+fn apply_onramp(accounts: &mut HashMap<Pubkey, AccountSharedData>, tx: &RampTx) -> bool {
+    let user_lamports = accounts
+        .get(&tx.user)
+        .map(|account| account.lamports())
+        .unwrap_or_default();
+    let Some(mut lamports) = user_lamports.checked_add(tx.amount) else {
+        return false;
+    };
+    if let Some(token) = &tx.token {
+        let Some(rent) = tokens::mint_mirrored(accounts, &tx.user, token, tx.amount) else {
+            return false;
+        };
+        lamports -= rent;
+    }
+
+    accounts
+        .entry(tx.user)
+        .or_insert_with(|| AccountSharedData::new(0, 0, &system_program::id()))
+        .set_lamports(lamports);
+    true
+}
+
+/// Writes a withdrawal receipt holding the lamports it records
+fn insert_receipt(
+    accounts: &mut HashMap<Pubkey, AccountSharedData>,
+    receipt: Pubkey,
+    receipt_data: &WithdrawalReceipt,
+) {
+    let data = borsh::to_vec(receipt_data).unwrap();
+    let mut receipt_account =
+        AccountSharedData::new(receipt_data.amount, data.len(), &WITHDRAWAL_RECEIPT_OWNER);
+    receipt_account.set_data_from_slice(&data);
+    accounts.insert(receipt, receipt_account);
+}
+
+/// Sanitizes the transaction and checks that every signature is valid for its
+/// message, so that accounts can only be debited by their owners. Signatures
+/// are verified with `verify_strict`, as the bank does on Solana, rejecting
+/// malleable ones. The addresses of v0 messages are loaded from the lookup
+/// tables in the state.
+fn sanitize_transaction(
+    tx: VersionedTransaction,
+    address_loader: StateAddressLoader,
+    reserved_account_keys: &ReservedAccountKeys,
+) -> transaction::Result<SanitizedTransaction> {
+    tx.sanitize()?;
+    let message_hash = tx.verify_and_hash_message()?;
+    SanitizedTransaction::try_create(
+        tx,
+        MessageHash::Precomputed(message_hash),
+        None,
+        address_loader,
+        &reserved_account_keys.active,
+    )
+}
+
+/// Loads the addresses of v0 messages from the lookup tables in the rollup
+/// state, as the bank does on Solana
+#[derive(Clone, Copy)]
+struct StateAddressLoader<'a> {
+    mock_bank: &'a MockBankCallback,
+    slot: Slot,
+    slot_hashes: &'a SlotHashes,
+}
+
+impl AddressLoader for StateAddressLoader<'_> {
+    fn load_addresses(
+        self,
+        lookups: &[MessageAddressTableLookup],
+    ) -> Result<LoadedAddresses, AddressLoaderError> {
+        lookups
+            .iter()
+            .map(|lookup| {
+                self.load_lookup_table_addresses(lookup)
+                    .map_err(|err| match err {
+                        AddressLookupError::LookupTableAccountNotFound => {
+                            AddressLoaderError::LookupTableAccountNotFound
+                        }
+                        AddressLookupError::InvalidAccountOwner => {
+                            AddressLoaderError::InvalidAccountOwner
+                        }
+                        AddressLookupError::InvalidAccountData => {
+                            AddressLoaderError::InvalidAccountData
+                        }
+                        AddressLookupError::InvalidLookupIndex => {
+                            AddressLoaderError::InvalidLookupIndex
+                        }
+                    })
+            })
+            .collect()
+    }
+}
+
+impl StateAddressLoader<'_> {
+    fn load_lookup_table_addresses(
+        &self,
+        lookup: &MessageAddressTableLookup,
+    ) -> Result<LoadedAddresses, AddressLookupError> {
+        let table_account = self
+            .mock_bank
+            .get_account_shared_data(&lookup.account_key)
+            .ok_or(AddressLookupError::LookupTableAccountNotFound)?;
+        if !address_lookup_table::program::check_id(table_account.owner()) {
+            return Err(AddressLookupError::InvalidAccountOwner);
+        }
+
+        // Addresses are only usable from the slot after they were added, and
+        // deactivated tables until their deactivation slot leaves the slot hashes
+        let table = AddressLookupTable::deserialize(table_account.data())
+            .map_err(|_| AddressLookupError::InvalidAccountData)?;
+        Ok(LoadedAddresses {
+            writable: table.lookup(self.slot, &lookup.writable_indexes, self.slot_hashes)?,
+            readonly: table.lookup(self.slot, &lookup.readonly_indexes, self.slot_hashes)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshDeserialize;
+    use solana_sdk::{
+        address_lookup_table::{state::LookupTableMeta, AddressLookupTableAccount},
+        bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+        instruction::Instruction,
+        message::{v0, VersionedMessage},
+        native_token::LAMPORTS_PER_SOL,
+        rent::Rent,
+        signature::{Keypair, Signature, Signer},
+        system_instruction,
+        transaction::Transaction,
+    };
+    use std::borrow::Cow;
+    use svm_runner_types::{BlockHeader, FeeConfig, SerializableAccount, TokenAmount};
+
+    const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+    const COLLECTOR: Pubkey = Pubkey::new_from_array([1; 32]);
+
+    fn system_account(lamports: u64) -> SerializableAccount {
+        SerializableAccount {
+            lamports,
+            owner: system_program::id(),
+            ..SerializableAccount::default()
+        }
+    }
+
+    fn rollup_state(accounts: Vec<(Pubkey, SerializableAccount)>) -> RollupState {
+        RollupState {
+            states: accounts
+                .into_iter()
+                .map(|(pubkey, account)| State { pubkey, account })
+                .collect(),
+        }
+    }
+
+    fn input(accounts: RollupState, slot: u64, txs: &[VersionedTransaction]) -> ExecutionInput {
+        ExecutionInput {
+            accounts,
+            txs: bincode::serialize(txs).unwrap(),
+            ramp_txs: vec![],
+            forced_txs: vec![],
+            forced_txs_start: 0,
+            forced_txs_prior_hash: Hash::default(),
+            header: BlockHeader {
+                slot,
+                unix_timestamp: 0,
+                epoch: 0,
+            },
+            fees: FeeConfig {
+                lamports_per_signature: LAMPORTS_PER_SIGNATURE,
+                collector: COLLECTOR,
+            },
+            batch_number: slot,
+        }
+    }
+
+    /// Blockhash referenced by the transactions of the batch executed on `state`
+    fn latest_blockhash(state: &RollupState) -> Hash {
+        let status = state
+            .states
+            .iter()
+            .find(|state| state.pubkey == ROLLUP_STATUS_ADDRESS)
+            .map(|state| &state.account);
+        RollupStatus::from_account(status, &hash_state(state.clone()))
+            .unwrap()
+            .latest_blockhash()
+    }
+
+    /// Transaction paid by the first signer
+    fn transaction(
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+        blockhash: Hash,
+    ) -> VersionedTransaction {
+        Transaction::new_signed_with_payer(
+            instructions,
+            Some(&signers[0].pubkey()),
+            signers,
+            blockhash,
+        )
+        .into()
+    }
+
+    fn account<'a>(output: &'a RunnerOutput, pubkey: &Pubkey) -> Option<&'a SerializableAccount> {
+        output
+            .state
+            .states
+            .iter()
+            .find(|state| state.pubkey == *pubkey)
+            .map(|state| &state.account)
+    }
+
+    fn lamports(output: &RunnerOutput, pubkey: &Pubkey) -> Option<u64> {
+        account(output, pubkey).map(|account| account.lamports)
+    }
+
+    #[test]
+    fn test_dependent_transfers() {
+        let alice = Keypair::new();
+        let bob = Keypair::new();
+        let carol = Pubkey::new_unique();
+        let state = rollup_state(vec![(
+            alice.pubkey(),
+            system_account(10 * LAMPORTS_PER_SOL),
+        )]);
+        let blockhash = latest_blockhash(&state);
+
+        // Bob only has lamports to send once the first transfer is executed
+        let txs = [
+            transaction(
+                &[system_instruction::transfer(
+                    &alice.pubkey(),
+                    &bob.pubkey(),
+                    2 * LAMPORTS_PER_SOL,
+                )],
+                &[&alice],
+                blockhash,
+            ),
+            transaction(
+                &[system_instruction::transfer(
+                    &bob.pubkey(),
+                    &carol,
+                    LAMPORTS_PER_SOL,
+                )],
+                &[&bob],
+                blockhash,
+            ),
+        ];
+        let output = runner(input(state, 1, &txs)).unwrap();
+
+        assert_eq!(output.transaction_results, vec![Ok(()), Ok(())]);
+        assert_eq!(
+            lamports(&output, &alice.pubkey()),
+            Some(8 * LAMPORTS_PER_SOL - LAMPORTS_PER_SIGNATURE)
+        );
+        assert_eq!(
+            lamports(&output, &bob.pubkey()),
+            Some(LAMPORTS_PER_SOL - LAMPORTS_PER_SIGNATURE)
+        );
+        assert_eq!(lamports(&output, &carol), Some(LAMPORTS_PER_SOL));
+    }
+
+    #[test]
+    fn test_canonical_state_and_zero_lamport_pruning() {
+        let alice = Keypair::new();
+        let bob = Pubkey::new_unique();
+        let dave = Pubkey::new_unique();
+        let state = rollup_state(vec![
+            (dave, system_account(LAMPORTS_PER_SOL)),
+            (alice.pubkey(), system_account(LAMPORTS_PER_SOL)),
+        ]);
+        let blockhash = latest_blockhash(&state);
+
+        // Alice sends everything left after the fee, emptying her account
+        let tx = transaction(
+            &[system_instruction::transfer(
+                &alice.pubkey(),
+                &bob,
+                LAMPORTS_PER_SOL - LAMPORTS_PER_SIGNATURE,
+            )],
+            &[&alice],
+            blockhash,
+        );
+        let output = runner(input(state.clone(), 1, &[tx.clone()])).unwrap();
+
+        assert_eq!(output.transaction_results, vec![Ok(())]);
+        assert_eq!(lamports(&output, &alice.pubkey()), None);
+        assert_eq!(lamports(&output, &dave), Some(LAMPORTS_PER_SOL));
+
+        // Accounts are sorted by pubkey, without the sysvars and builtins of
+        // the bank, and the same batch always results in the same state
+        let mut pubkeys = vec![bob, dave, COLLECTOR, ROLLUP_STATUS_ADDRESS];
+        pubkeys.sort();
+        assert_eq!(
+            output
+                .state
+                .states
+                .iter()
+                .map(|state| state.pubkey)
+                .collect::<Vec<_>>(),
+            pubkeys
+        );
+        let again = runner(input(state, 1, &[tx])).unwrap();
+        assert_eq!(hash_state(again.state), hash_state(output.state));
+    }
+
+    #[test]
+    fn test_runner_with_accounts_matches_runner() {
+        let alice = Keypair::new();
+        let bob = Pubkey::new_unique();
+        let carol = Pubkey::new_unique();
+        let dave = Pubkey::new_unique();
+        let state = rollup_state(vec![
+            (dave, system_account(LAMPORTS_PER_SOL)),
+            (alice.pubkey(), system_account(LAMPORTS_PER_SOL)),
+        ]);
+        let tx = transaction(
+            &[system_instruction::transfer(
+                &alice.pubkey(),
+                &bob,
+                LAMPORTS_PER_SOL - LAMPORTS_PER_SIGNATURE,
+            )],
+            &[&alice],
+            latest_blockhash(&state),
+        );
+        let mut batch = input(state.clone(), 1, &[tx]);
+        batch.ramp_txs = vec![RampTx {
+            is_onramp: true,
+            user: carol,
+            amount: LAMPORTS_PER_SOL,
+            token: Some(TokenAmount {
+                mint: Pubkey::new_unique(),
+                decimals: 6,
+                amount: 1_000,
+            }),
+        }];
+        let output = runner(batch.clone()).unwrap();
+
+        // The accounts are only read from the base, never copied up front
+        let base = MockBankCallback::default();
+        for state in &state.states {
+            base.account_shared_data
+                .write()
+                .unwrap()
+                .insert(state.pubkey, state.account.clone().into());
+        }
+        batch.accounts = RollupState { states: vec![] };
+        let lazy =
+            runner_with_accounts(std::sync::Arc::new(base), hash_state(state.clone()), &batch)
+                .unwrap();
+
+        assert_eq!(lazy.ramp_results, output.ramp_results);
+        assert_eq!(lazy.transaction_results, output.transaction_results);
+        assert_eq!(lazy.blockhash, output.blockhash);
+        assert!(lazy.updates.iter().all(|(pubkey, _)| *pubkey != dave));
+        assert!(lazy
+            .updates
+            .contains(&(alice.pubkey(), None::<SerializableAccount>)));
+
+        let mut accounts = state
+            .states
+            .into_iter()
+            .map(|state| (state.pubkey, state.account))
+            .collect::<BTreeMap<_, _>>();
+        for (pubkey, account) in lazy.updates {
+            match account {
+                Some(account) => accounts.insert(pubkey, account),
+                None => accounts.remove(&pubkey),
+            };
+        }
+        assert_eq!(
+            hash_state(rollup_state(accounts.into_iter().collect())),
+            hash_state(output.state)
+        );
+    }
+
+    #[test]
+    fn test_bad_signatures_rejected() {
+        let alice = Keypair::new();
+        let mallory = Keypair::new();
+        let bob = Pubkey::new_unique();
+        let state = rollup_state(vec![(alice.pubkey(), system_account(LAMPORTS_PER_SOL))]);
+        let blockhash = latest_blockhash(&state);
+        let instructions = [system_instruction::transfer(
+            &alice.pubkey(),
+            &bob,
+            LAMPORTS_PER_SOL / 2,
+        )];
+
+        // Signed by another key than the fee payer
+        let mut forged = transaction(&instructions, &[&alice], blockhash);
+        forged.signatures[0] = mallory.sign_message(&forged.message.serialize());
+        // Valid signature with a flipped bit
+        let mut tampered = transaction(&instructions, &[&alice], blockhash);
+        let mut signature = <[u8; 64]>::try_from(tampered.signatures[0].as_ref()).unwrap();
+        signature[63] ^= 1;
+        tampered.signatures[0] = Signature::from(signature);
+
+        let output = runner(input(state, 1, &[forged, tampered])).unwrap();
+
+        assert_eq!(
+            output.transaction_results,
+            vec![Err(TransactionError::SignatureFailure); 2]
+        );
+        assert_eq!(lamports(&output, &alice.pubkey()), Some(LAMPORTS_PER_SOL));
+        assert_eq!(lamports(&output, &bob), None);
+        // Rejected transactions get a receipt, but pay no fee
+        assert_eq!(output.receipts.len(), 2);
+        assert!(output
+            .receipts
+            .iter()
+            .all(|receipt| receipt.error.is_some() && receipt.fee == 0));
+    }
+
+    #[test]
+    fn test_ramp_underflow_and_failed_onramp_refund() {
+        let alice = Pubkey::new_unique();
+        let bob = Pubkey::new_unique();
+        let state = rollup_state(vec![(alice, system_account(LAMPORTS_PER_SOL))]);
+        let token = TokenAmount {
+            mint: Pubkey::new_unique(),
+            decimals: 6,
+            amount: 1_000,
+        };
+
+        let mut batch = input(state, 1, &[]);
+        batch.ramp_txs = vec![
+            // Alice can't withdraw more than she has
+            RampTx {
+                is_onramp: false,
+                user: alice,
+                amount: 2 * LAMPORTS_PER_SOL,
+                token: None,
+            },
+            // Too few lamports to pay the rent of the mint and token account
+            RampTx {
+                is_onramp: true,
+                user: bob,
+                amount: 1,
+                token: Some(token.clone()),
+            },
+            RampTx {
+                is_onramp: false,
+                user: alice,
+                amount: LAMPORTS_PER_SOL / 2,
+                token: None,
+            },
+        ];
+        let output = runner(batch).unwrap();
+        let receipt = |index| withdrawal_receipt_address(&output.prior_state_root, index);
+
+        assert_eq!(output.ramp_results, vec![false, false, true]);
+        assert_eq!(lamports(&output, &alice), Some(LAMPORTS_PER_SOL / 2));
+        assert_eq!(account(&output, &receipt(0)), None);
+        assert_eq!(lamports(&output, &receipt(2)), Some(LAMPORTS_PER_SOL / 2));
+
+        // The tokens are refunded with a receipt, the lamports being refunded
+        // on L1 from the ramp result
+        let refund = account(&output, &receipt(1)).unwrap();
+        assert_eq!(refund.lamports, 0);
+        assert_eq!(refund.owner, WITHDRAWAL_RECEIPT_OWNER);
+        assert_eq!(
+            WithdrawalReceipt::try_from_slice(&refund.data).unwrap(),
+            WithdrawalReceipt {
+                user: bob,
+                amount: 0,
+                token: Some(token.clone()),
+                prior_state_root: output.prior_state_root,
+                index: 1,
+            }
+        );
+        assert_eq!(account(&output, &bob), None);
+        assert!(tokens::token_accounts(&bob, &token)
+            .iter()
+            .all(|pubkey| account(&output, pubkey).is_none()));
+    }
+
+    #[test]
+    fn test_durable_nonce_advance() {
+        let alice = Keypair::new();
+        let bob = Pubkey::new_unique();
+        let nonce = Pubkey::new_unique();
+        // The nonce stands for a blockhash the rollup never produced
+        let durable_nonce = DurableNonce::from_blockhash(&Hash::new_unique());
+        let nonce_account = AccountSharedData::new_data(
+            LAMPORTS_PER_SOL,
+            &NonceVersions::new(NonceState::new_initialized(
+                &alice.pubkey(),
+                durable_nonce,
+                LAMPORTS_PER_SIGNATURE,
+            )),
+            &system_program::id(),
+        )
+        .unwrap();
+        let state = rollup_state(vec![
+            (alice.pubkey(), system_account(LAMPORTS_PER_SOL)),
+            (nonce, nonce_account.into()),
+        ]);
+        let blockhash = latest_blockhash(&state);
+
+        let tx = transaction(
+            &[
+                system_instruction::advance_nonce_account(&nonce, &alice.pubkey()),
+                system_instruction::transfer(&alice.pubkey(), &bob, LAMPORTS_PER_SOL / 2),
+            ],
+            &[&alice],
+            *durable_nonce.as_hash(),
+        );
+        let output = runner(input(state, 1, &[tx.clone()])).unwrap();
+
+        assert_eq!(output.transaction_results, vec![Ok(())]);
+        assert_eq!(lamports(&output, &bob), Some(LAMPORTS_PER_SOL / 2));
+        let nonce_account = AccountSharedData::from(account(&output, &nonce).unwrap().clone());
+        let versions: NonceVersions = nonce_account.state().unwrap();
+        let NonceState::Initialized(nonce_data) = versions.state() else {
+            panic!("uninitialized nonce account");
+        };
+        assert_eq!(
+            nonce_data.durable_nonce,
+            DurableNonce::from_blockhash(&blockhash)
+        );
+
+        // The nonce was advanced, so the transaction can't be replayed
+        let output = runner(input(output.state, 2, &[tx])).unwrap();
+        assert_eq!(
+            output.transaction_results,
+            vec![Err(TransactionError::BlockhashNotFound)]
+        );
+    }
+
+    #[test]
+    fn test_address_lookup_table_resolution() {
+        let alice = Keypair::new();
+        let bob = Pubkey::new_unique();
+        let table = Pubkey::new_unique();
+        let table_account = SerializableAccount {
+            lamports: LAMPORTS_PER_SOL,
+            data: AddressLookupTable {
+                meta: LookupTableMeta {
+                    authority: Some(alice.pubkey()),
+                    ..LookupTableMeta::default()
+                },
+                addresses: Cow::Owned(vec![bob]),
+            }
+            .serialize_for_tests()
+            .unwrap(),
+            owner: address_lookup_table::program::id(),
+            ..SerializableAccount::default()
+        };
+        let state = rollup_state(vec![
+            (alice.pubkey(), system_account(LAMPORTS_PER_SOL)),
+            (table, table_account),
+        ]);
+
+        let message = v0::Message::try_compile(
+            &alice.pubkey(),
+            &[system_instruction::transfer(
+                &alice.pubkey(),
+                &bob,
+                LAMPORTS_PER_SOL / 2,
+            )],
+            &[AddressLookupTableAccount {
+                key: table,
+                addresses: vec![bob],
+            }],
+            latest_blockhash(&state),
+        )
+        .unwrap();
+        // Bob is only referenced through the lookup table
+        assert!(!message.account_keys.contains(&bob));
+        let tx = VersionedTransaction::try_new(VersionedMessage::V0(message), &[&alice]).unwrap();
+        let output = runner(input(state, 1, &[tx])).unwrap();
+
+        assert_eq!(output.transaction_results, vec![Ok(())]);
+        assert_eq!(lamports(&output, &bob), Some(LAMPORTS_PER_SOL / 2));
+    }
+
+    #[test]
+    fn test_deploy_then_invoke_in_next_batch() {
+        const CHUNK_SIZE: usize = 900;
+        let alice = Keypair::new();
+        let buffer = Keypair::new();
+        let program = Keypair::new();
+        let elf = data::load_program("noop");
+        let rent = Rent::default();
+        let state = rollup_state(vec![(
+            alice.pubkey(),
+            system_account(100 * LAMPORTS_PER_SOL),
+        )]);
+        let blockhash = latest_blockhash(&state);
+
+        let mut txs = vec![transaction(
+            &bpf_loader_upgradeable::create_buffer(
+                &alice.pubkey(),
+                &buffer.pubkey(),
+                &alice.pubkey(),
+                rent.minimum_balance(UpgradeableLoaderState::size_of_buffer(elf.len())),
+                elf.len(),
+            )
+            .unwrap(),
+            &[&alice, &buffer],
+            blockhash,
+        )];
+        for (index, chunk) in elf.chunks(CHUNK_SIZE).enumerate() {
+            txs.push(transaction(
+                &[bpf_loader_upgradeable::write(
+                    &buffer.pubkey(),
+                    &alice.pubkey(),
+                    (index * CHUNK_SIZE) as u32,
+                    chunk.to_vec(),
+                )],
+                &[&alice],
+                blockhash,
+            ));
+        }
+        txs.push(transaction(
+            &bpf_loader_upgradeable::deploy_with_max_program_len(
+                &alice.pubkey(),
+                &program.pubkey(),
+                &buffer.pubkey(),
+                &alice.pubkey(),
+                rent.minimum_balance(UpgradeableLoaderState::size_of_program()),
+                elf.len(),
+            )
+            .unwrap(),
+            &[&alice, &program],
+            blockhash,
+        ));
+        let invoke = |blockhash| {
+            transaction(
+                &[Instruction::new_with_bytes(program.pubkey(), &[], vec![])],
+                &[&alice],
+                blockhash,
+            )
+        };
+        // Programs deployed in a batch can only be invoked from the next slot
+        txs.push(invoke(blockhash));
+        let output = runner(input(state, 1, &txs)).unwrap();
+
+        let (invoked, deployed) = output.transaction_results.split_last().unwrap();
+        assert!(deployed.iter().all(Result::is_ok), "{deployed:?}");
+        assert!(invoked.is_err());
+
+        let output = runner(input(output.state, 2, &[invoke(output.blockhash)])).unwrap();
+        assert_eq!(output.transaction_results, vec![Ok(())]);
+    }
+
+    #[test]
+    fn test_fees_credited_to_collector() {
+        let alice = Keypair::new();
+        let bob = Pubkey::new_unique();
+        let state = rollup_state(vec![(alice.pubkey(), system_account(LAMPORTS_PER_SOL))]);
+        let blockhash = latest_blockhash(&state);
+
+        let txs = [
+            transaction(
+                &[system_instruction::transfer(
+                    &alice.pubkey(),
+                    &bob,
+                    LAMPORTS_PER_SOL / 2,
+                )],
+                &[&alice],
+                blockhash,
+            ),
+            // Fails for lack of lamports, but still pays the fee
+            transaction(
+                &[system_instruction::transfer(
+                    &alice.pubkey(),
+                    &bob,
+                    LAMPORTS_PER_SOL,
+                )],
+                &[&alice],
+                blockhash,
+            ),
+        ];
+        let output = runner(input(state.clone(), 1, &txs)).unwrap();
+
+        assert!(output.transaction_results[0].is_ok());
+        assert!(output.transaction_results[1].is_err());
+        let fees = output
+            .receipts
+            .iter()
+            .map(|receipt| receipt.fee)
+            .sum::<u64>();
+        assert_eq!(fees, 2 * LAMPORTS_PER_SIGNATURE);
+        assert_eq!(lamports(&output, &COLLECTOR), Some(fees));
+        assert_eq!(
+            lamports(&output, &alice.pubkey()),
+            Some(LAMPORTS_PER_SOL / 2 - fees)
+        );
+
+        // Lamports only move between accounts
+        let total = |state: &RollupState| {
+            state
+                .states
+                .iter()
+                .map(|state| state.account.lamports)
+                .sum::<u64>()
+        };
+        assert_eq!(total(&output.state), total(&state));
     }
 }
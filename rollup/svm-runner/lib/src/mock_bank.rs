@@ -1,50 +1,53 @@
 #[allow(deprecated)]
 use solana_sdk::sysvar::recent_blockhashes::{Entry as BlockhashesEntry, RecentBlockhashes};
 use {
-    crate::data::load_program,
-    // solana_account::AccountSharedData,
-    solana_bpf_loader_program::syscalls::{
-        Blake3Hasher, Keccak256Hasher, Sha256Hasher, SyscallAbort, SyscallAllocFree,
-        SyscallAltBn128, SyscallAltBn128Compression, SyscallCreateProgramAddress,
-        SyscallCurveGroupOps, SyscallCurveMultiscalarMultiplication, SyscallCurvePointValidation,
-        SyscallGetClockSysvar, SyscallGetRentSysvar, SyscallGetReturnData, SyscallGetStackHeight,
-        SyscallHash, SyscallInvokeSignedRust, SyscallLog, SyscallLogBpfComputeUnits,
-        SyscallLogData, SyscallLogPubkey, SyscallLogU64, SyscallMemcmp, SyscallMemcpy,
-        SyscallMemmove, SyscallMemset, SyscallPanic, SyscallPoseidon, SyscallRemainingComputeUnits,
-        SyscallSecp256k1Recover, SyscallSetReturnData, SyscallTryFindProgramAddress,
-    },
+    serde::Serialize,
+    solana_bpf_loader_program::syscalls::create_program_runtime_environment_v1,
     solana_compute_budget::compute_budget::ComputeBudget,
-    solana_feature_set::FeatureSet,
+    solana_feature_set::{bpf_account_data_direct_mapping, FeatureSet},
     solana_program_runtime::{
-        invoke_context::InvokeContext,
         loaded_programs::{
             BlockRelation, ForkGraph, ProgramCache, ProgramCacheEntry, ProgramRuntimeEnvironments,
         },
         solana_rbpf::{
-            program::{BuiltinFunction, BuiltinProgram, FunctionRegistry},
+            program::{BuiltinProgram, FunctionRegistry},
             vm::Config,
         },
     },
     solana_sdk::{
         account::{AccountSharedData, ReadableAccount, WritableAccount},
-        // account::{ReadableAccount, WritableAccount},
-        bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+        bpf_loader, bpf_loader_upgradeable,
         clock::{Clock, UnixTimestamp},
+        epoch_rewards::EpochRewards,
+        epoch_schedule::EpochSchedule,
         native_loader,
         pubkey::Pubkey,
         rent::Rent,
-        slot_hashes::Slot,
-        sysvar::SysvarId,
+        slot_hashes::{Slot, SlotHashes},
+        sysvar::{last_restart_slot::LastRestartSlot, SysvarId},
+        transaction::SanitizedTransaction,
     },
     solana_svm::{
+        rollback_accounts::RollbackAccounts,
         transaction_processing_callback::{AccountState, TransactionProcessingCallback},
+        transaction_processing_result::{ProcessedTransaction, TransactionProcessingResult},
         transaction_processor::TransactionBatchProcessor,
     },
     solana_type_overrides::sync::{Arc, RwLock},
-    std::{cmp::Ordering, collections::HashMap},
+    std::{
+        cmp::Ordering,
+        collections::{hash_map::Entry, HashMap},
+    },
+    svm_runner_types::{BlockHeader, RollupStatus},
 };
 
-pub const WALLCLOCK_TIME: i64 = 1704067200; // Arbitrarily Jan 1, 2024
+/// Features active on the rollup. Every feature is enabled, except account
+/// data direct mapping which is not active on mainnet yet.
+pub fn rollup_feature_set() -> FeatureSet {
+    let mut feature_set = FeatureSet::all_enabled();
+    feature_set.deactivate(&bpf_account_data_direct_mapping::id());
+    feature_set
+}
 
 pub struct MockForkGraph {}
 
@@ -58,10 +61,17 @@ impl ForkGraph for MockForkGraph {
     }
 }
 
+/// Accounts a batch is executed against
+pub type AccountsBase = std::sync::Arc<dyn TransactionProcessingCallback + Send + Sync>;
+
+/// Bank holding the accounts written by the batch. Accounts it doesn't hold
+/// yet are read from `base`, if any, so that a batch can be executed against
+/// a large state without copying it.
 #[derive(Default, Clone)]
 pub struct MockBankCallback {
     pub feature_set: Arc<FeatureSet>,
     pub account_shared_data: Arc<RwLock<HashMap<Pubkey, AccountSharedData>>>,
+    pub base: Option<AccountsBase>,
     #[allow(clippy::type_complexity)]
     pub inspected_accounts:
         Arc<RwLock<HashMap<Pubkey, Vec<(Option<AccountSharedData>, /* is_writable */ bool)>>>>,
@@ -69,23 +79,23 @@ pub struct MockBankCallback {
 
 impl TransactionProcessingCallback for MockBankCallback {
     fn account_matches_owners(&self, account: &Pubkey, owners: &[Pubkey]) -> Option<usize> {
-        if let Some(data) = self.account_shared_data.read().unwrap().get(account) {
-            if data.lamports() == 0 {
-                None
-            } else {
-                owners.iter().position(|entry| data.owner() == entry)
-            }
-        } else {
-            None
-        }
+        self.get_account_shared_data(account)
+            .filter(|account| account.lamports() != 0)
+            .and_then(|account| owners.iter().position(|entry| account.owner() == entry))
     }
 
     fn get_account_shared_data(&self, pubkey: &Pubkey) -> Option<AccountSharedData> {
-        self.account_shared_data
+        let account = self
+            .account_shared_data
             .read()
             .unwrap()
             .get(pubkey)
-            .cloned()
+            .cloned();
+        account.or_else(|| {
+            self.base
+                .as_ref()
+                .and_then(|base| base.get_account_shared_data(pubkey))
+        })
     }
 
     fn add_builtin_account(&self, name: &str, program_id: &Pubkey) {
@@ -111,142 +121,102 @@ impl TransactionProcessingCallback for MockBankCallback {
 }
 
 impl MockBankCallback {
-    #[allow(unused)]
-    pub fn override_feature_set(&mut self, new_set: FeatureSet) {
-        self.feature_set = Arc::new(new_set)
-    }
-}
-
-#[allow(unused)]
-pub fn program_address(program_name: &str) -> Pubkey {
-    let mut name = program_name;
-    let len = program_name.len();
-    if len > 32 {
-        name = &program_name[0..32];
+    /// Copies the accounts held by the base into the bank, so that they can
+    /// be modified in place
+    pub fn load_accounts(&self, pubkeys: impl IntoIterator<Item = Pubkey>) {
+        let Some(base) = &self.base else {
+            return;
+        };
+        let mut accounts = self.account_shared_data.write().unwrap();
+        for pubkey in pubkeys {
+            if let Entry::Vacant(entry) = accounts.entry(pubkey) {
+                if let Some(account) = base.get_account_shared_data(&pubkey) {
+                    entry.insert(account);
+                }
+            }
+        }
     }
-    Pubkey::create_with_seed(&Pubkey::default(), name, &Pubkey::default()).unwrap()
-}
-
-#[allow(unused)]
-pub fn deploy_program(name: String, deployment_slot: Slot, mock_bank: &MockBankCallback) -> Pubkey {
-    let program_account = program_address(&name);
-    let program_data_account = bpf_loader_upgradeable::get_program_data_address(&program_account);
-
-    let state = UpgradeableLoaderState::Program {
-        programdata_address: program_data_account,
-    };
 
-    // The program account must have funds and hold the executable binary
-    let mut account_data = AccountSharedData::default();
-    account_data.set_data(bincode::serialize(&state).unwrap());
-    account_data.set_lamports(25);
-    account_data.set_owner(bpf_loader_upgradeable::id());
-    account_data.set_executable(true);
-    mock_bank
-        .account_shared_data
-        .write()
-        .unwrap()
-        .insert(program_account, account_data);
+    /// Stores the accounts left behind by a processed transaction so that the
+    /// next transaction loads them. Successful transactions store every
+    /// writable account, failed ones only the fee payer and nonce rollbacks.
+    ///
+    /// Returns the addresses of the stored accounts.
+    pub fn commit_transaction(
+        &self,
+        tx: &SanitizedTransaction,
+        processing_result: &TransactionProcessingResult,
+    ) -> Vec<Pubkey> {
+        let Ok(processed_tx) = processing_result else {
+            // Transactions that could not be processed leave no trace
+            return vec![];
+        };
 
-    let mut account_data = AccountSharedData::default();
-    let state = UpgradeableLoaderState::ProgramData {
-        slot: deployment_slot,
-        upgrade_authority_address: None,
-    };
-    let mut header = bincode::serialize(&state).unwrap();
-    let mut complement = vec![
-        0;
-        std::cmp::max(
-            0,
-            UpgradeableLoaderState::size_of_programdata_metadata().saturating_sub(header.len())
-        )
-    ];
-    let mut buffer = load_program(&name);
-    header.append(&mut complement);
-    header.append(&mut buffer.to_vec());
-    account_data.set_data(header);
-    mock_bank
-        .account_shared_data
-        .write()
-        .unwrap()
-        .insert(program_data_account, account_data);
+        let mut accounts = self.account_shared_data.write().unwrap();
+        let rollback_accounts = match processed_tx {
+            ProcessedTransaction::Executed(executed_tx) if executed_tx.was_successful() => {
+                let mut stored = vec![];
+                for (index, (pubkey, account)) in
+                    executed_tx.loaded_transaction.accounts.iter().enumerate()
+                {
+                    if tx.message().is_writable(index) {
+                        accounts.insert(*pubkey, account.clone());
+                        stored.push(*pubkey);
+                    }
+                }
+                return stored;
+            }
+            ProcessedTransaction::Executed(executed_tx) => {
+                &executed_tx.loaded_transaction.rollback_accounts
+            }
+            ProcessedTransaction::FeesOnly(fees_only_tx) => &fees_only_tx.rollback_accounts,
+        };
 
-    program_account
+        match rollback_accounts {
+            RollbackAccounts::FeePayerOnly { fee_payer_account } => {
+                accounts.insert(*tx.message().fee_payer(), fee_payer_account.clone());
+                vec![*tx.message().fee_payer()]
+            }
+            RollbackAccounts::SameNonceAndFeePayer { nonce } => {
+                accounts.insert(*nonce.address(), nonce.account().clone());
+                vec![*nonce.address()]
+            }
+            RollbackAccounts::SeparateNonceAndFeePayer {
+                nonce,
+                fee_payer_account,
+            } => {
+                accounts.insert(*nonce.address(), nonce.account().clone());
+                accounts.insert(*tx.message().fee_payer(), fee_payer_account.clone());
+                vec![*nonce.address(), *tx.message().fee_payer()]
+            }
+        }
+    }
 }
 
-// #[allow(unused)]
-// pub fn create_executable_environment(
-//     fork_graph: Arc<RwLock<MockForkGraph>>,
-//     mock_bank: &MockBankCallback,
-//     program_cache: &mut ProgramCache<MockForkGraph>,
-// ) {
-//     const DEPLOYMENT_EPOCH: u64 = 0;
-//     const DEPLOYMENT_SLOT: u64 = 0;
-
-//     program_cache.environments = ProgramRuntimeEnvironments {
-//         program_runtime_v1: Arc::new(create_custom_environment()),
-//         // We are not using program runtime v2
-//         program_runtime_v2: Arc::new(BuiltinProgram::new_loader(
-//             Config::default(),
-//             FunctionRegistry::default(),
-//         )),
-//     };
-
-//     program_cache.fork_graph = Some(Arc::downgrade(&fork_graph));
-
-//     // We must fill in the sysvar cache entries
-
-//     // clock contents are important because we use them for a sysvar loading test
-//     let clock = Clock {
-//         slot: DEPLOYMENT_SLOT,
-//         epoch_start_timestamp: WALLCLOCK_TIME.saturating_sub(10) as UnixTimestamp,
-//         epoch: DEPLOYMENT_EPOCH,
-//         leader_schedule_epoch: DEPLOYMENT_EPOCH,
-//         unix_timestamp: WALLCLOCK_TIME as UnixTimestamp,
-//     };
-
-//     let mut account_data = AccountSharedData::default();
-//     account_data.set_data(bincode::serialize(&clock).unwrap());
-//     mock_bank
-//         .account_shared_data
-//         .write()
-//         .unwrap()
-//         .insert(Clock::id(), account_data);
-
-//     // default rent is fine
-//     let rent = Rent::default();
-
-//     let mut account_data = AccountSharedData::default();
-//     account_data.set_data(bincode::serialize(&rent).unwrap());
-//     mock_bank
-//         .account_shared_data
-//         .write()
-//         .unwrap()
-//         .insert(Rent::id(), account_data);
-
-//     // SystemInstruction::AdvanceNonceAccount asserts RecentBlockhashes is non-empty
-//     // but then just gets the blockhash from InvokeContext. so the sysvar doesnt need real entries
-//     #[allow(deprecated)]
-//     let recent_blockhashes = vec![BlockhashesEntry::default()];
-
-//     let mut account_data = AccountSharedData::default();
-//     account_data.set_data(bincode::serialize(&recent_blockhashes).unwrap());
-//     #[allow(deprecated)]
-//     mock_bank
-//         .account_shared_data
-//         .write()
-//         .unwrap()
-//         .insert(RecentBlockhashes::id(), account_data);
-// }
-
-#[allow(unused)]
+/// Sets up the program runtime and the sysvars for a batch executed in the
+/// block described by `header`. The clock must match the slot of the batch
+/// processor, so that programs deployed by the batch are recorded at this slot
+/// and only visible after it.
+///
+/// Syscalls are registered from the feature set of the bank, as on mainnet.
 pub fn create_executable_environment(
     fork_graph: Arc<RwLock<MockForkGraph>>,
     mock_bank: &MockBankCallback,
     program_cache: &mut ProgramCache<MockForkGraph>,
+    header: &BlockHeader,
+    status: &RollupStatus,
+    lamports_per_signature: u64,
 ) {
+    let program_runtime_v1 = create_program_runtime_environment_v1(
+        &mock_bank.feature_set,
+        &ComputeBudget::default(),
+        false, /* deployment */
+        false, /* debugging_features */
+    )
+    .expect("failed to create program runtime environment");
+
     program_cache.environments = ProgramRuntimeEnvironments {
-        program_runtime_v1: Arc::new(create_custom_environment()),
+        program_runtime_v1: Arc::new(program_runtime_v1),
         // We are not using program runtime v2
         program_runtime_v2: Arc::new(BuiltinProgram::new_loader(
             Config::default(),
@@ -256,55 +226,65 @@ pub fn create_executable_environment(
 
     program_cache.fork_graph = Some(Arc::downgrade(&fork_graph));
 
-    const EXECUTION_SLOT: u64 = 0;
-    const EXECUTION_EPOCH: u64 = 0;
-
     // We must fill in the sysvar cache entries
 
-    // clock contents are important because we use them for a sysvar loading test
     let clock = Clock {
-        slot: EXECUTION_SLOT,
-        epoch_start_timestamp: WALLCLOCK_TIME.saturating_sub(10) as UnixTimestamp,
-        epoch: EXECUTION_EPOCH,
-        leader_schedule_epoch: EXECUTION_EPOCH,
-        unix_timestamp: WALLCLOCK_TIME as UnixTimestamp,
+        slot: header.slot,
+        epoch_start_timestamp: header.unix_timestamp as UnixTimestamp,
+        epoch: header.epoch,
+        leader_schedule_epoch: header.epoch,
+        unix_timestamp: header.unix_timestamp as UnixTimestamp,
     };
-
-    let mut account_data = AccountSharedData::default();
-    account_data.set_data(bincode::serialize(&clock).unwrap());
-    mock_bank
-        .account_shared_data
-        .write()
-        .unwrap()
-        .insert(Clock::id(), account_data);
+    set_sysvar(mock_bank, Clock::id(), &clock);
 
     // default rent is fine
-    let rent = Rent::default();
+    set_sysvar(mock_bank, Rent::id(), &Rent::default());
 
-    let mut account_data = AccountSharedData::default();
-    account_data.set_data(bincode::serialize(&rent).unwrap());
-    mock_bank
-        .account_shared_data
-        .write()
-        .unwrap()
-        .insert(Rent::id(), account_data);
+    // Epochs have a fixed length from genesis, the sequencer deriving the epoch
+    // of the header from the same schedule
+    set_sysvar(
+        mock_bank,
+        EpochSchedule::id(),
+        &EpochSchedule::without_warmup(),
+    );
 
-    // SystemInstruction::AdvanceNonceAccount asserts RecentBlockhashes is non-empty
-    // but then just gets the blockhash from InvokeContext. so the sysvar doesnt need real entries
+    // There are no staking rewards nor restarts on the rollup
+    set_sysvar(mock_bank, EpochRewards::id(), &EpochRewards::default());
+    set_sysvar(
+        mock_bank,
+        LastRestartSlot::id(),
+        &LastRestartSlot::default(),
+    );
+
+    // Blockhashes of the previous batches, never empty as the genesis status
+    // has one, which SystemInstruction::AdvanceNonceAccount requires
+    #[allow(deprecated)]
+    let recent_blockhashes = status
+        .blockhashes
+        .iter()
+        .map(|(_, blockhash)| BlockhashesEntry::new(blockhash, lamports_per_signature))
+        .collect::<Vec<_>>();
     #[allow(deprecated)]
-    let recent_blockhashes = vec![BlockhashesEntry::default()];
+    set_sysvar(mock_bank, RecentBlockhashes::id(), &recent_blockhashes);
+
+    set_sysvar(
+        mock_bank,
+        SlotHashes::id(),
+        &SlotHashes::new(&status.blockhashes),
+    );
+}
 
+/// Stores the sysvar in its account, from which the sysvar cache is filled
+fn set_sysvar(mock_bank: &MockBankCallback, id: Pubkey, sysvar: &impl Serialize) {
     let mut account_data = AccountSharedData::default();
-    account_data.set_data(bincode::serialize(&recent_blockhashes).unwrap());
-    #[allow(deprecated)]
+    account_data.set_data(bincode::serialize(sysvar).unwrap());
     mock_bank
         .account_shared_data
         .write()
         .unwrap()
-        .insert(RecentBlockhashes::id(), account_data);
+        .insert(id, account_data);
 }
 
-#[allow(unused)]
 pub fn register_builtins(
     mock_bank: &MockBankCallback,
     batch_processor: &TransactionBatchProcessor<MockForkGraph>,
@@ -324,6 +304,20 @@ pub fn register_builtins(
         ),
     );
 
+    // Programs deployed in the genesis, such as SPL Token, are owned by the
+    // non-upgradeable loader
+    let bpf_loader_name = "solana_bpf_loader_program";
+    batch_processor.add_builtin(
+        mock_bank,
+        bpf_loader::id(),
+        bpf_loader_name,
+        ProgramCacheEntry::new_builtin(
+            DEPLOYMENT_SLOT,
+            bpf_loader_name.len(),
+            solana_bpf_loader_program::Entrypoint::vm,
+        ),
+    );
+
     // In order to perform a transference of native tokens using the system instruction,
     // the system program builtin must be registered.
     let system_program_name = "system_program";
@@ -337,170 +331,32 @@ pub fn register_builtins(
             solana_system_program::system_processor::Entrypoint::vm,
         ),
     );
-}
-
-#[allow(unused)]
-fn create_custom_environment<'a>() -> BuiltinProgram<InvokeContext<'a>> {
-    let compute_budget = ComputeBudget::default();
-    let vm_config = Config {
-        max_call_depth: compute_budget.max_call_depth,
-        stack_frame_size: compute_budget.stack_frame_size,
-        enable_address_translation: true,
-        enable_stack_frame_gaps: true,
-        instruction_meter_checkpoint_distance: 10000,
-        enable_instruction_meter: true,
-        enable_instruction_tracing: true,
-        enable_symbol_and_section_labels: true,
-        reject_broken_elfs: true,
-        noop_instruction_rate: 256,
-        sanitize_user_provided_values: true,
-        external_internal_function_hash_collision: false,
-        reject_callx_r10: true,
-        enable_sbpf_v1: true,
-        enable_sbpf_v2: false,
-        optimize_rodata: false,
-        aligned_memory_mapping: true,
-    };
-    // let vm_config = Config {
-    //     max_call_depth: compute_budget.max_call_depth,
-    //     stack_frame_size: compute_budget.stack_frame_size,
-    //     enable_address_translation: true,
-    //     enable_stack_frame_gaps: true,
-    //     instruction_meter_checkpoint_distance: 10000,
-    //     enable_instruction_meter: true,
-    //     enable_instruction_tracing: true,
-    //     enable_symbol_and_section_labels: true,
-    //     reject_broken_elfs: true,
-    //     noop_instruction_rate: 256,
-    //     sanitize_user_provided_values: true,
-    //     enabled_sbpf_versions: SBPFVersion::V1..=SBPFVersion::V1,
-    //     optimize_rodata: false,
-    //     aligned_memory_mapping: true,
-    // };
-
-    // These functions are system calls the compile contract calls during execution, so they
-    // need to be registered.
-    let mut function_registry = FunctionRegistry::<BuiltinFunction<InvokeContext>>::default();
-    function_registry
-        .register_function_hashed(*b"abort", SyscallAbort::vm)
-        .expect("Registration failed");
-    function_registry
-        .register_function_hashed(*b"sol_log_", SyscallLog::vm)
-        .expect("Registration failed");
-    function_registry
-        .register_function_hashed(*b"sol_memcpy_", SyscallMemcpy::vm)
-        .expect("Registration failed");
-    function_registry
-        .register_function_hashed(*b"sol_memset_", SyscallMemset::vm)
-        .expect("Registration failed");
-    function_registry
-        .register_function_hashed(*b"sol_invoke_signed_rust", SyscallInvokeSignedRust::vm)
-        .expect("Registration failed");
-    function_registry
-        .register_function_hashed(*b"sol_set_return_data", SyscallSetReturnData::vm)
-        .expect("Registration failed");
-    function_registry
-        .register_function_hashed(*b"sol_get_clock_sysvar", SyscallGetClockSysvar::vm)
-        .expect("Registration failed");
-    function_registry
-        .register_function_hashed(*b"sol_get_rent_sysvar", SyscallGetRentSysvar::vm)
-        .expect("Registration failed");
-    function_registry
-        .register_function_hashed(*b"sol_alt_bn128_group_op", SyscallAltBn128::vm)
-        .expect("Registration failed");
-    function_registry
-        .register_function_hashed(
-            *b"sol_alt_bn128_compression",
-            SyscallAltBn128Compression::vm,
-        )
-        .expect("Registration failed");
-    function_registry
-        .register_function_hashed(*b"sol_memmove_", SyscallMemmove::vm)
-        .expect("Registration failed");
-    function_registry
-        .register_function_hashed(*b"sol_memcmp_", SyscallMemcmp::vm)
-        .expect("Registration failed");
-    function_registry
-        .register_function_hashed(*b"sol_alloc_free_", SyscallAllocFree::vm)
-        .expect("Registration failed");
-    function_registry
-        .register_function_hashed(*b"sol_secp256k1_recover", SyscallSecp256k1Recover::vm)
-        .expect("Registration failed");
-
-    // Panic
-    function_registry
-        .register_function_hashed(*b"sol_panic_", SyscallPanic::vm)
-        .expect("Registration failed");
-
-    // Logging
-    function_registry
-        .register_function_hashed(*b"sol_log_64_", SyscallLogU64::vm)
-        .expect("Registration failed");
-    function_registry
-        .register_function_hashed(*b"sol_log_compute_units_", SyscallLogBpfComputeUnits::vm)
-        .expect("Registration failed");
-    function_registry
-        .register_function_hashed(*b"sol_log_pubkey", SyscallLogPubkey::vm)
-        .expect("Registration failed");
-    function_registry
-        .register_function_hashed(*b"sol_log_data", SyscallLogData::vm)
-        .expect("Registration failed");
-    function_registry
-        .register_function_hashed(
-            *b"sol_create_program_address",
-            SyscallCreateProgramAddress::vm,
-        )
-        .expect("Registration failed");
-    function_registry
-        .register_function_hashed(
-            *b"sol_try_find_program_address",
-            SyscallTryFindProgramAddress::vm,
-        )
-        .expect("Registration failed");
-
-    // Sha256
-    function_registry
-        .register_function_hashed(*b"sol_sha256", SyscallHash::vm::<Sha256Hasher>)
-        .expect("Registration failed");
-
-    // Keccak256
-    function_registry
-        .register_function_hashed(*b"sol_keccak256", SyscallHash::vm::<Keccak256Hasher>)
-        .expect("Registration failed");
 
-    function_registry
-        .register_function_hashed(
-            *b"sol_curve_validate_point",
-            SyscallCurvePointValidation::vm,
-        )
-        .expect("Registration failed");
-    function_registry
-        .register_function_hashed(*b"sol_curve_group_op", SyscallCurveGroupOps::vm)
-        .expect("Registration failed");
-    function_registry
-        .register_function_hashed(
-            *b"sol_curve_multiscalar_mul",
-            SyscallCurveMultiscalarMultiplication::vm,
-        )
-        .expect("Registration failed");
-    function_registry
-        .register_function_hashed(*b"sol_poseidon", SyscallPoseidon::vm)
-        .expect("Registration failed");
-    function_registry
-        .register_function_hashed(
-            *b"sol_remaining_compute_units",
-            SyscallRemainingComputeUnits::vm,
-        )
-        .expect("Registration failed");
-    function_registry
-        .register_function_hashed(*b"sol_blake3", SyscallHash::vm::<Blake3Hasher>)
-        .expect("Registration failed");
-    function_registry
-        .register_function_hashed(*b"sol_get_return_data", SyscallGetReturnData::vm)
-        .expect("Registration failed");
-    function_registry
-        .register_function_hashed(*b"sol_get_stack_height", SyscallGetStackHeight::vm)
-        .expect("Registration failed");
+    // Lookup tables are created and extended through the builtin program, and
+    // resolved from the state when sanitizing v0 transactions
+    let address_lookup_table_program_name = "address_lookup_table_program";
+    batch_processor.add_builtin(
+        mock_bank,
+        solana_sdk::address_lookup_table::program::id(),
+        address_lookup_table_program_name,
+        ProgramCacheEntry::new_builtin(
+            DEPLOYMENT_SLOT,
+            address_lookup_table_program_name.len(),
+            solana_address_lookup_table_program::processor::Entrypoint::vm,
+        ),
+    );
 
-    BuiltinProgram::new_loader(vm_config, function_registry)
+    // Compute budget instructions are processed before execution to set the
+    // compute limits and priority fee, but must still be executable.
+    let compute_budget_program_name = "compute_budget_program";
+    batch_processor.add_builtin(
+        mock_bank,
+        solana_sdk::compute_budget::id(),
+        compute_budget_program_name,
+        ProgramCacheEntry::new_builtin(
+            DEPLOYMENT_SLOT,
+            compute_budget_program_name.len(),
+            solana_compute_budget_program::Entrypoint::vm,
+        ),
+    );
 }
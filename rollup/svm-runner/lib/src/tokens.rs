@@ -0,0 +1,139 @@
+//! Mirrored SPL tokens, minted and burned by ramp txs
+use solana_sdk::{
+    account::{AccountSharedData, ReadableAccount, WritableAccount},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::state::{Account as TokenAccount, AccountState, Mint};
+use std::collections::HashMap;
+use svm_runner_types::{mirrored_mint_address, TokenAmount, MIRRORED_MINT_AUTHORITY};
+
+/// Accounts written when moving `token` in or out of the rollup for `user`:
+/// the mirrored mint and the associated token account of the user
+pub fn token_accounts(user: &Pubkey, token: &TokenAmount) -> [Pubkey; 2] {
+    let mint = mirrored_mint_address(&token.mint);
+    [mint, get_associated_token_address(user, &mint)]
+}
+
+/// Mints the mirrored `token` to the associated token account of `user`,
+/// creating the mint and the token account if needed. Their rent is paid out of
+/// `lamports`, returning the lamports spent or `None` if nothing was minted.
+pub fn mint_mirrored(
+    accounts: &mut HashMap<Pubkey, AccountSharedData>,
+    user: &Pubkey,
+    token: &TokenAmount,
+    lamports: u64,
+) -> Option<u64> {
+    let [mint_address, token_account_address] = token_accounts(user, token);
+    let rent = Rent::default();
+    let mut rent_paid = 0;
+
+    let (mut mint_account, mut mint) = match accounts.get(&mint_address) {
+        Some(account) => (account.clone(), unpack_mint(account)?),
+        None => {
+            let account_rent = rent.minimum_balance(Mint::LEN);
+            rent_paid += account_rent;
+            let mint = Mint {
+                mint_authority: COption::Some(MIRRORED_MINT_AUTHORITY),
+                supply: 0,
+                decimals: token.decimals,
+                is_initialized: true,
+                freeze_authority: COption::None,
+            };
+            let account = AccountSharedData::new(account_rent, Mint::LEN, &spl_token::id());
+            (account, mint)
+        }
+    };
+    let (mut token_account, mut state) = match accounts.get(&token_account_address) {
+        Some(account) => (
+            account.clone(),
+            unpack_token_account(account, &mint_address)?,
+        ),
+        None => {
+            let account_rent = rent.minimum_balance(TokenAccount::LEN);
+            rent_paid += account_rent;
+            let state = TokenAccount {
+                mint: mint_address,
+                owner: *user,
+                state: AccountState::Initialized,
+                ..TokenAccount::default()
+            };
+            let account = AccountSharedData::new(account_rent, TokenAccount::LEN, &spl_token::id());
+            (account, state)
+        }
+    };
+
+    if rent_paid > lamports || state.is_frozen() {
+        return None;
+    }
+    mint.supply = mint.supply.checked_add(token.amount)?;
+    state.amount = state.amount.checked_add(token.amount)?;
+
+    Mint::pack(mint, mint_account.data_as_mut_slice()).ok()?;
+    TokenAccount::pack(state, token_account.data_as_mut_slice()).ok()?;
+    accounts.insert(mint_address, mint_account);
+    accounts.insert(token_account_address, token_account);
+
+    Some(rent_paid)
+}
+
+/// Burns the mirrored `token` from the associated token account of `user`,
+/// returning whether it held enough of it
+pub fn burn_mirrored(
+    accounts: &mut HashMap<Pubkey, AccountSharedData>,
+    user: &Pubkey,
+    token: &TokenAmount,
+) -> bool {
+    let [mint_address, token_account_address] = token_accounts(user, token);
+    let (Some(mut mint_account), Some(mut token_account)) = (
+        accounts.get(&mint_address).cloned(),
+        accounts.get(&token_account_address).cloned(),
+    ) else {
+        return false;
+    };
+    let (Some(mut mint), Some(mut state)) = (
+        unpack_mint(&mint_account),
+        unpack_token_account(&token_account, &mint_address),
+    ) else {
+        return false;
+    };
+    // The owner of an associated token account can be changed with spl-token
+    if state.owner != *user || state.is_frozen() {
+        return false;
+    }
+    let (Some(supply), Some(amount)) = (
+        mint.supply.checked_sub(token.amount),
+        state.amount.checked_sub(token.amount),
+    ) else {
+        return false;
+    };
+    mint.supply = supply;
+    state.amount = amount;
+    state.delegated_amount = state.delegated_amount.min(amount);
+
+    Mint::pack(mint, mint_account.data_as_mut_slice()).unwrap();
+    TokenAccount::pack(state, token_account.data_as_mut_slice()).unwrap();
+    accounts.insert(mint_address, mint_account);
+    accounts.insert(token_account_address, token_account);
+
+    true
+}
+
+fn unpack_mint(account: &AccountSharedData) -> Option<Mint> {
+    if !spl_token::check_id(account.owner()) {
+        return None;
+    }
+    Mint::unpack(account.data()).ok()
+}
+
+fn unpack_token_account(account: &AccountSharedData, mint: &Pubkey) -> Option<TokenAccount> {
+    if !spl_token::check_id(account.owner()) {
+        return None;
+    }
+    TokenAccount::unpack(account.data())
+        .ok()
+        .filter(|state| state.mint == *mint)
+}
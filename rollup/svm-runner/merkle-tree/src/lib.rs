@@ -1,148 +1,181 @@
+//! Sparse Merkle tree over Solana accounts, keyed by pubkey.
+//!
+//! The position of a leaf is given by the bits of its pubkey, most significant
+//! bit first. Subtrees are compacted: an empty subtree hashes to
+//! [`EMPTY_HASH`] and a subtree holding a single account is replaced by the
+//! leaf itself, so the tree only has as many levels as needed to separate its
+//! keys.
 use bincode::serialize;
 use serde::{Deserialize, Serialize};
 use solana_account::Account;
 use solana_hash::Hash;
 use solana_pubkey::Pubkey;
 use solana_sha256_hasher::hashv;
-
-// /// Represents a Solana account in the Merkle Tree
-// #[derive(Serialize, Deserialize, Clone, Debug)]
-// pub struct SolanaAccount {
-//     pub lamports: u64,
-//     pub data: Vec<u8>,
-//     pub executable: bool,
-//     pub rent_epoch: u64,
-//     pub owner: Pubkey,
-// }
-
-#[derive(Clone, Debug, Deserialize, Serialize)]
-pub struct MerkleTree {
-    pub leaves: Vec<(Pubkey, Hash)>, // Tree leaves with Pubkey and Hash
-    pub tree: Vec<Hash>,             // All tree nodes (including leaves)
-    pub root: Hash,                  // Tree root
+use std::collections::BTreeMap;
+
+mod list;
+mod proof;
+pub use list::*;
+pub use proof::*;
+
+/// Hash of an empty subtree
+pub const EMPTY_HASH: Hash = Hash::new_from_array([0; 32]);
+
+/// Domain separators, so that a leaf can never be mistaken for a node
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+/// Number of bits in a key, and so maximum depth of the tree
+pub const KEY_BITS: usize = 256;
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct SparseMerkleTree {
+    /// Hash of the account stored at each pubkey
+    leaves: BTreeMap<Pubkey, Hash>,
+    /// Root of the tree, cleared when leaves are updated
+    #[serde(skip)]
+    root: Option<Hash>,
 }
 
-impl MerkleTree {
+impl SparseMerkleTree {
     pub fn new() -> Self {
-        MerkleTree {
-            leaves: Vec::new(),
-            tree: Vec::new(),
-            root: Hash::default(),
-        }
+        Self::default()
     }
 
     /// Hash of a Solana account
-    fn hash_account(account: &Account) -> Hash {
+    pub fn hash_account(account: &Account) -> Hash {
         let account_bytes = serialize(account).unwrap();
         hashv(&[&account_bytes])
     }
 
-    /// Inserts an account into the Merkle tree
-    pub fn insert(&mut self, pubkey: Pubkey, account: &Account) {
-        let account_hash = Self::hash_account(account);
-        self.leaves.push((pubkey, account_hash));
-        self.build_tree();
+    /// Hash of a leaf holding the account hash `value` at `key`
+    pub fn hash_leaf(key: &Pubkey, value: &Hash) -> Hash {
+        hashv(&[LEAF_PREFIX, key.as_ref(), value.as_ref()])
     }
 
-    /// Builds the tree from leaves
-    fn build_tree(&mut self) {
-        self.tree.clear();
-
-        if self.leaves.is_empty() {
-            self.root = Hash::default();
-            return;
-        }
-
-        // Copy leaf hashes into the tree
-        let mut current_level: Vec<Hash> = self.leaves.iter().map(|(_, hash)| *hash).collect();
-
-        // Ensure even number of leaves by duplicating last if necessary
-        if current_level.len() % 2 != 0 {
-            current_level.push(*current_level.last().unwrap());
-        }
-
-        self.tree.extend_from_slice(&current_level);
-
-        while current_level.len() > 1 {
-            let mut next_level = Vec::new();
+    /// Hash two nodes to generate a parent node
+    pub fn hash_nodes(left: &Hash, right: &Hash) -> Hash {
+        hashv(&[NODE_PREFIX, left.as_ref(), right.as_ref()])
+    }
 
-            for chunk in current_level.chunks(2) {
-                let left = &chunk[0];
-                let right = &chunk[1];
-                let parent = Self::hash_nodes(left, right);
-                next_level.push(parent);
-            }
+    /// Inserts or replaces the account stored at `pubkey`
+    pub fn insert(&mut self, pubkey: Pubkey, account: &Account) {
+        self.leaves.insert(pubkey, Self::hash_account(account));
+        self.root = None;
+    }
 
-            // Ensure even number of nodes at each level by duplicating last if necessary
-            if next_level.len() % 2 != 0 && next_level.len() > 1 {
-                next_level.push(*next_level.last().unwrap());
-            }
+    /// Removes the account stored at `pubkey`, returning its hash if it existed
+    pub fn remove(&mut self, pubkey: &Pubkey) -> Option<Hash> {
+        self.root = None;
+        self.leaves.remove(pubkey)
+    }
 
-            self.tree.extend_from_slice(&next_level);
-            current_level = next_level;
+    /// Applies a batch of updates, `None` removing the account. The root is
+    /// only recomputed once, when it is next requested.
+    pub fn update<'a>(&mut self, updates: impl IntoIterator<Item = (Pubkey, Option<&'a Account>)>) {
+        for (pubkey, account) in updates {
+            match account {
+                Some(account) => self.leaves.insert(pubkey, Self::hash_account(account)),
+                None => self.leaves.remove(&pubkey),
+            };
         }
-
-        self.root = current_level[0];
+        self.root = None;
     }
 
-    /// Hash two nodes to generate a parent node
-    fn hash_nodes(left: &Hash, right: &Hash) -> Hash {
-        hashv(&[left.as_ref(), right.as_ref()])
+    /// Hash of the account stored at `pubkey`
+    pub fn get(&self, pubkey: &Pubkey) -> Option<&Hash> {
+        self.leaves.get(pubkey)
     }
 
-    /// Returns the current tree root
-    pub fn get_root(&self) -> Hash {
-        self.root
+    pub fn len(&self) -> usize {
+        self.leaves.len()
     }
 
-    /// Generates a Merkle proof for a given public key
-    pub fn generate_proof(&self, pubkey: &Pubkey) -> Option<Vec<Hash>> {
-        let mut index = self.leaves.iter().position(|(pk, _)| pk == pubkey)?;
-        let mut proof = Vec::new();
-        let mut current_level = self
-            .leaves
-            .iter()
-            .map(|(_, hash)| *hash)
-            .collect::<Vec<_>>();
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
 
-        if current_level.len() % 2 != 0 {
-            current_level.push(*current_level.last().unwrap());
+    /// Returns the current tree root
+    pub fn get_root(&mut self) -> Hash {
+        if let Some(root) = self.root {
+            return root;
         }
+        let root = self.compute_root();
+        self.root = Some(root);
+        root
+    }
 
-        let mut level_start = 0;
-        let mut level_size = current_level.len();
-
-        while level_size > 1 {
-            let is_right_node = index % 2 == 1;
-            let sibling_index = if is_right_node { index - 1 } else { index + 1 };
+    /// Computes the tree root without caching it
+    pub fn compute_root(&self) -> Hash {
+        let leaves = self.leaves.iter().collect::<Vec<_>>();
+        Self::subtree_root(&leaves, 0)
+    }
 
-            if sibling_index < level_size {
-                proof.push(current_level[sibling_index]);
+    /// Root of the subtree holding `leaves`, all sharing their first `depth` bits
+    fn subtree_root(leaves: &[(&Pubkey, &Hash)], depth: usize) -> Hash {
+        match leaves {
+            [] => EMPTY_HASH,
+            [(key, value)] => Self::hash_leaf(key, value),
+            _ => {
+                let (left, right) = Self::split(leaves, depth);
+                Self::hash_nodes(
+                    &Self::subtree_root(left, depth + 1),
+                    &Self::subtree_root(right, depth + 1),
+                )
             }
+        }
+    }
 
-            index /= 2;
-            level_start += level_size;
-            level_size = (level_size + 1) / 2; // If odd, last element is copied
-
-            let mut next_level = Vec::new();
-            for chunk in current_level.chunks(2) {
-                let left = &chunk[0];
-                let right = &chunk[1];
-                let parent = Self::hash_nodes(left, right);
-                next_level.push(parent);
-            }
+    /// Splits sorted leaves between the left and right children at `depth`
+    fn split<'a, 'b>(
+        leaves: &'a [(&'b Pubkey, &'b Hash)],
+        depth: usize,
+    ) -> (&'a [(&'b Pubkey, &'b Hash)], &'a [(&'b Pubkey, &'b Hash)]) {
+        let index = leaves.partition_point(|(key, _)| !bit(key, depth));
+        leaves.split_at(index)
+    }
 
-            if next_level.len() % 2 != 0 && next_level.len() > 1 {
-                next_level.push(*next_level.last().unwrap());
+    /// Generates an inclusion proof if an account is stored at `pubkey`, or an
+    /// exclusion proof otherwise
+    pub fn generate_proof(&self, pubkey: &Pubkey) -> SparseMerkleProof {
+        let leaves = self.leaves.iter().collect::<Vec<_>>();
+        let mut current = leaves.as_slice();
+        let mut siblings = Vec::new();
+
+        for depth in 0..KEY_BITS {
+            match current {
+                [] => break,
+                [(key, value)] => {
+                    return SparseMerkleProof {
+                        leaf: Some((**key, **value)),
+                        siblings,
+                    }
+                }
+                _ => {
+                    let (left, right) = Self::split(current, depth);
+                    if bit(pubkey, depth) {
+                        siblings.push(Self::subtree_root(left, depth + 1));
+                        current = right;
+                    } else {
+                        siblings.push(Self::subtree_root(right, depth + 1));
+                        current = left;
+                    }
+                }
             }
-
-            current_level = next_level;
         }
 
-        Some(proof)
+        SparseMerkleProof {
+            leaf: None,
+            siblings,
+        }
     }
 }
 
+/// Bit of `key` at `depth`, most significant bit first
+pub(crate) fn bit(key: &Pubkey, depth: usize) -> bool {
+    key.as_ref()[depth / 8] & (0x80 >> (depth % 8)) != 0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -160,228 +193,162 @@ mod tests {
     }
 
     #[test]
-    fn test_single_leaf_duplication() {
-        let mut merkle_tree = MerkleTree::new();
-
-        // Insert a single leaf
-        let pubkey1 = Keypair::new().pubkey();
-        let account1 = create_example_account(pubkey1);
-        merkle_tree.insert(pubkey1, &account1);
-
-        // Verify that the tree contains two copies of the leaf hash
-        assert_eq!(merkle_tree.tree.len(), 3); // 2 leaves (duplicated) + 1 root
-        assert_eq!(merkle_tree.tree[0], merkle_tree.tree[1]); // First two entries should be identical
-
-        // Verify that proof can be generated
-        let proof = merkle_tree.generate_proof(&pubkey1);
-        assert!(proof.is_some());
-
-        // Proof should contain a single element (the duplicated hash)
-        let proof_vec = proof.unwrap();
-        assert_eq!(proof_vec.len(), 1);
+    fn test_empty_tree() {
+        let mut merkle_tree = SparseMerkleTree::new();
+        assert_eq!(merkle_tree.get_root(), EMPTY_HASH);
+
+        let pubkey = Keypair::new().pubkey();
+        let proof = merkle_tree.generate_proof(&pubkey);
+        assert!(proof.siblings.is_empty());
+        assert!(verify_proof(&EMPTY_HASH, &pubkey, None, &proof));
     }
 
     #[test]
-    fn test_insert_and_build_tree() {
-        let mut merkle_tree = MerkleTree::new();
+    fn test_single_leaf_is_root() {
+        let mut merkle_tree = SparseMerkleTree::new();
 
-        let pubkey1 = Keypair::new().pubkey(); // Generate a valid pubkey
+        let pubkey1 = Keypair::new().pubkey();
         let account1 = create_example_account(pubkey1);
         merkle_tree.insert(pubkey1, &account1);
 
-        let pubkey2 = Keypair::new().pubkey(); // Generate another valid pubkey
-        let account2 = create_example_account(pubkey2);
-        merkle_tree.insert(pubkey2, &account2);
-
-        // Verify that root is generated after insertion
-        assert_ne!(
+        // A lone leaf is not padded, it is the root itself
+        let account_hash = SparseMerkleTree::hash_account(&account1);
+        assert_eq!(
             merkle_tree.get_root(),
-            Hash::default(),
-            "Root should not be empty."
+            SparseMerkleTree::hash_leaf(&pubkey1, &account_hash)
         );
+
+        let proof = merkle_tree.generate_proof(&pubkey1);
+        assert!(proof.siblings.is_empty());
+        assert!(verify_proof(
+            &merkle_tree.get_root(),
+            &pubkey1,
+            Some(&account1),
+            &proof
+        ));
     }
 
     #[test]
-    fn test_generate_proof() {
-        let mut merkle_tree = MerkleTree::new();
-
-        let pubkey1 = Pubkey::new_unique();
-        let account1 = Account {
-            lamports: 1000,
-            data: vec![1, 2, 3],
-            executable: false,
-            rent_epoch: 0,
-            owner: pubkey1,
-        };
-        merkle_tree.insert(pubkey1, &account1);
+    fn test_root_is_independent_of_insertion_order() {
+        let accounts = (0..8)
+            .map(|i| {
+                let pubkey = Pubkey::new_unique();
+                let mut account = create_example_account(pubkey);
+                account.lamports = i;
+                (pubkey, account)
+            })
+            .collect::<Vec<_>>();
 
-        let pubkey2 = Pubkey::new_unique();
-        let account2 = Account {
-            lamports: 2000,
-            data: vec![4, 5, 6],
-            executable: false,
-            rent_epoch: 0,
-            owner: pubkey2,
-        };
-        merkle_tree.insert(pubkey2, &account2);
+        let mut merkle_tree = SparseMerkleTree::new();
+        for (pubkey, account) in accounts.iter() {
+            merkle_tree.insert(*pubkey, account);
+        }
 
-        println!("tree after insert pubkey2: {:?}", merkle_tree.tree);
+        let mut reversed_tree = SparseMerkleTree::new();
+        reversed_tree.update(
+            accounts
+                .iter()
+                .rev()
+                .map(|(pubkey, account)| (*pubkey, Some(account))),
+        );
 
-        let pubkey3 = Pubkey::new_unique();
-        let account3 = Account {
-            lamports: 2000,
-            data: vec![3, 3, 3],
-            executable: false,
-            rent_epoch: 0,
-            owner: pubkey3,
-        };
-        merkle_tree.insert(pubkey3, &account3);
-
-        let pubkey4 = Pubkey::new_unique();
-        let account4 = Account {
-            lamports: 2000,
-            data: vec![4, 4, 4],
-            executable: false,
-            rent_epoch: 0,
-            owner: pubkey4,
-        };
-        merkle_tree.insert(pubkey4, &account4);
-
-        let pubkey5 = Pubkey::new_unique();
-        let account5 = Account {
-            lamports: 2000,
-            data: vec![5, 5, 5],
-            executable: false,
-            rent_epoch: 0,
-            owner: pubkey5,
-        };
-        merkle_tree.insert(pubkey5, &account5);
-
-        let pubkey6 = Pubkey::new_unique();
-        let account6 = Account {
-            lamports: 2000,
-            data: vec![6, 6, 6],
-            executable: false,
-            rent_epoch: 0,
-            owner: pubkey6,
-        };
-        merkle_tree.insert(pubkey6, &account6);
-
-        let pubkey7 = Pubkey::new_unique();
-        let account7 = Account {
-            lamports: 2000,
-            data: vec![7, 7, 7],
-            executable: false,
-            rent_epoch: 0,
-            owner: pubkey7,
-        };
-        merkle_tree.insert(pubkey7, &account7);
-
-        let pubkey8 = Pubkey::new_unique();
-        let account8 = Account {
-            lamports: 2000,
-            data: vec![8, 8, 8],
-            executable: false,
-            rent_epoch: 0,
-            owner: pubkey8,
-        };
-        merkle_tree.insert(pubkey8, &account8);
-
-        println!("tree after insert pubkey8: {:?}", merkle_tree.tree);
-
-        let proof = merkle_tree.generate_proof(&pubkey3);
-        assert!(proof.is_some(), "Merkle proof should be generated.");
-        let proof_vec = proof.unwrap();
-        println!("proof for pubkey3: {:?}", proof_vec);
-        assert!(!proof_vec.is_empty(), "Merkle proof should not be empty.");
+        assert_eq!(merkle_tree.get_root(), reversed_tree.get_root());
     }
 
     #[test]
-    fn test_root_changes_after_insert() {
-        let mut merkle_tree = MerkleTree::new();
+    fn test_root_changes_after_update() {
+        let mut merkle_tree = SparseMerkleTree::new();
 
-        let pubkey1 = Keypair::new().pubkey(); // Generate a valid pubkey
-        let account1 = create_example_account(pubkey1);
+        let pubkey1 = Keypair::new().pubkey();
+        let mut account1 = create_example_account(pubkey1);
+        merkle_tree.insert(pubkey1, &account1);
+        let pubkey2 = Keypair::new().pubkey();
+        merkle_tree.insert(pubkey2, &create_example_account(pubkey2));
         let initial_root = merkle_tree.get_root();
 
-        // Add first account
+        account1.lamports += 1;
         merkle_tree.insert(pubkey1, &account1);
+        let updated_root = merkle_tree.get_root();
+        assert_ne!(updated_root, initial_root);
 
-        // Verify that root changed after insertion
-        assert_ne!(
-            merkle_tree.get_root(),
-            initial_root,
-            "Root should change after insertion."
-        );
-
-        // Add another account
-        let pubkey2 = Keypair::new().pubkey(); // Generate another valid pubkey
-        let account2 = create_example_account(pubkey2);
-        merkle_tree.insert(pubkey2, &account2);
-
-        // Verify that root changes again
-        assert_ne!(
-            merkle_tree.get_root(),
-            initial_root,
-            "Root should change again after another insertion."
-        );
+        merkle_tree.remove(&pubkey1);
+        assert_ne!(merkle_tree.get_root(), updated_root);
+        assert_eq!(merkle_tree.len(), 1);
     }
 
-    // #[test]
-    // fn test_find_leaf_index() {
-    //     let mut merkle_tree = MerkleTree::new();
-
-    //     let pubkey1 = Keypair::new().pubkey(); // Generate a valid pubkey
-    //     let account1 = create_example_account(pubkey1);
-    //     merkle_tree.insert(pubkey1, &account1);
-
-    //     let pubkey2 = Keypair::new().pubkey(); // Generate another valid pubkey
-    //     let account2 = create_example_account(pubkey2);
-    //     merkle_tree.insert(pubkey2, &account2);
-
-    //     // Verify that leaf indices are correct
-    //     let index1 = merkle_tree.find_leaf_index(&pubkey1);
-    //     let index2 = merkle_tree.find_leaf_index(&pubkey2);
-
-    //     assert_eq!(index1, Some(0), "Index of pubkey1 should be 0.");
-    //     assert_eq!(index2, Some(1), "Index of pubkey2 should be 1.");
-    // }
-
     #[test]
-    fn test_generate_proof_invalid_pubkey() {
-        let mut merkle_tree = MerkleTree::new();
-
-        let pubkey1 = Keypair::new().pubkey(); // Generate a valid pubkey
-        let account1 = create_example_account(pubkey1);
-        merkle_tree.insert(pubkey1, &account1);
-
-        let invalid_pubkey = Keypair::new().pubkey(); // Generate an invalid pubkey
-
-        // Verify that proof fails for a pubkey that doesn't exist in the tree
-        let proof = merkle_tree.generate_proof(&invalid_pubkey);
-
-        assert!(
-            proof.is_none(),
-            "Proof for an invalid pubkey should be None."
+    fn test_inclusion_proofs() {
+        let mut merkle_tree = SparseMerkleTree::new();
+        let accounts = (0..20)
+            .map(|_| {
+                let pubkey = Keypair::new().pubkey();
+                (pubkey, create_example_account(pubkey))
+            })
+            .collect::<Vec<_>>();
+        merkle_tree.update(
+            accounts
+                .iter()
+                .map(|(pubkey, account)| (*pubkey, Some(account))),
         );
+        let root = merkle_tree.get_root();
+
+        for (pubkey, account) in accounts.iter() {
+            let proof = merkle_tree.generate_proof(pubkey);
+            assert!(verify_proof(&root, pubkey, Some(account), &proof));
+            // The proof does not hold for another account or a missing one
+            let mut other_account = account.clone();
+            other_account.lamports += 1;
+            assert!(!verify_proof(&root, pubkey, Some(&other_account), &proof));
+            assert!(!verify_proof(&root, pubkey, None, &proof));
+        }
     }
-    // Not like that, we dedouble instead of considering account hash as root
-    // #[test]
-    // fn test_integrity_of_solana_account() {
-    //     let mut merkle_tree = MerkleTree::new();
-
-    //     let pubkey = Keypair::new().pubkey(); // Generate a valid pubkey
-    //     let account = create_example_account(pubkey);
-    //     merkle_tree.insert(pubkey, &account);
-    //     println!("Tree after insertion of pubkey: {:?}", merkle_tree.tree);
 
-    //     // Get tree root after insertion
-    //     let root = merkle_tree.get_root();
-
-    //     // Recreate account hash manually
-    //     let account_hash = MerkleTree::hash_account(&account);
+    #[test]
+    fn test_exclusion_proofs() {
+        let mut merkle_tree = SparseMerkleTree::new();
+        for _ in 0..20 {
+            let pubkey = Keypair::new().pubkey();
+            merkle_tree.insert(pubkey, &create_example_account(pubkey));
+        }
+        let root = merkle_tree.get_root();
+
+        for _ in 0..20 {
+            let missing = Keypair::new().pubkey();
+            let proof = merkle_tree.generate_proof(&missing);
+            assert!(verify_proof(&root, &missing, None, &proof));
+            assert!(!verify_proof(
+                &root,
+                &missing,
+                Some(&create_example_account(missing)),
+                &proof
+            ));
+        }
+    }
 
-    //     // Verify that account hash is correctly used as leaf in the tree
-    //     assert_eq!(root, account_hash, "Root does not match account hash.");
-    // }
+    #[test]
+    fn test_proof_serialization() {
+        let mut merkle_tree = SparseMerkleTree::new();
+        let pubkeys = (0..10).map(|_| Keypair::new().pubkey()).collect::<Vec<_>>();
+        for pubkey in pubkeys.iter() {
+            merkle_tree.insert(*pubkey, &create_example_account(*pubkey));
+        }
+        let root = merkle_tree.get_root();
+
+        let proof = merkle_tree.generate_proof(&pubkeys[3]);
+        let bytes = proof.to_bytes();
+        let decoded = SparseMerkleProof::try_from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, proof);
+        assert!(verify_proof(
+            &root,
+            &pubkeys[3],
+            Some(&create_example_account(pubkeys[3])),
+            &decoded
+        ));
+
+        // Truncated or padded proofs are rejected
+        assert!(SparseMerkleProof::try_from_bytes(&bytes[..bytes.len() - 1]).is_none());
+        let mut padded = bytes.clone();
+        padded.push(0);
+        assert!(SparseMerkleProof::try_from_bytes(&padded).is_none());
+    }
 }
@@ -0,0 +1,93 @@
+//! Binary Merkle tree over an ordered list of leaves, padded with
+//! [`EMPTY_HASH`] up to the next power of two. A leaf is proven by its index
+//! and the siblings along its path.
+use crate::{SparseMerkleTree, EMPTY_HASH, LEAF_PREFIX};
+use solana_hash::Hash;
+use solana_sha256_hasher::hashv;
+
+/// Hash of a leaf holding `data`
+pub fn hash_list_leaf(data: &[u8]) -> Hash {
+    hashv(&[LEAF_PREFIX, data])
+}
+
+/// Root of the tree over `leaves`, [`EMPTY_HASH`] if there are none
+pub fn list_root(leaves: &[Hash]) -> Hash {
+    let mut level = padded(leaves);
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| SparseMerkleTree::hash_nodes(&pair[0], &pair[1]))
+            .collect();
+    }
+    level.first().copied().unwrap_or(EMPTY_HASH)
+}
+
+/// Siblings along the path of the leaf at `index`, from the leaf up to the root
+pub fn list_proof(leaves: &[Hash], mut index: usize) -> Vec<Hash> {
+    let mut level = padded(leaves);
+    let mut siblings = Vec::new();
+    while level.len() > 1 {
+        siblings.push(level[index ^ 1]);
+        level = level
+            .chunks(2)
+            .map(|pair| SparseMerkleTree::hash_nodes(&pair[0], &pair[1]))
+            .collect();
+        index /= 2;
+    }
+    siblings
+}
+
+/// Verifies that `leaf` is at `index` in the tree of the given `root`
+pub fn verify_list_proof(root: &Hash, mut index: usize, leaf: &Hash, siblings: &[Hash]) -> bool {
+    if siblings.len() >= usize::BITS as usize || index >> siblings.len() != 0 {
+        return false;
+    }
+
+    let mut current = *leaf;
+    for sibling in siblings {
+        current = if index & 1 == 0 {
+            SparseMerkleTree::hash_nodes(&current, sibling)
+        } else {
+            SparseMerkleTree::hash_nodes(sibling, &current)
+        };
+        index /= 2;
+    }
+
+    current == *root
+}
+
+fn padded(leaves: &[Hash]) -> Vec<Hash> {
+    let mut level = leaves.to_vec();
+    if !level.is_empty() {
+        level.resize(level.len().next_power_of_two(), EMPTY_HASH);
+    }
+    level
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_proofs() {
+        assert_eq!(list_root(&[]), EMPTY_HASH);
+
+        for len in 1..10u8 {
+            let leaves = (0..len).map(|i| hash_list_leaf(&[i])).collect::<Vec<_>>();
+            let root = list_root(&leaves);
+
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = list_proof(&leaves, index);
+                assert!(verify_list_proof(&root, index, leaf, &proof));
+                // The leaf is not proven at another index
+                assert!(!verify_list_proof(&root, index ^ 1, leaf, &proof));
+                assert!(!verify_list_proof(
+                    &root,
+                    index + (1 << proof.len()),
+                    leaf,
+                    &proof
+                ));
+            }
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use crate::{bit, SparseMerkleTree, EMPTY_HASH, KEY_BITS};
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+use solana_account::Account;
+use solana_hash::Hash;
+use solana_pubkey::Pubkey;
+
+/// Proof that a pubkey holds a given account, or holds none, in a [SparseMerkleTree]
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct SparseMerkleProof {
+    /// Leaf found at the end of the path of the proven pubkey, as its key and
+    /// account hash. For an exclusion proof this is either none, when the path
+    /// ends in an empty subtree, or another leaf sharing the path.
+    pub leaf: Option<(Pubkey, Hash)>,
+    /// Siblings along the path, from the root down to the leaf
+    pub siblings: Vec<Hash>,
+}
+
+impl SparseMerkleProof {
+    /// Verifies the proof against `root`, `value` being the account hash stored
+    /// at `key` or none for an exclusion proof
+    pub fn verify(&self, root: &Hash, key: &Pubkey, value: Option<&Hash>) -> bool {
+        if self.siblings.len() > KEY_BITS {
+            return false;
+        }
+
+        let mut current = match (&self.leaf, value) {
+            (Some((leaf_key, leaf_value)), Some(value)) => {
+                if leaf_key != key || leaf_value != value {
+                    return false;
+                }
+                SparseMerkleTree::hash_leaf(leaf_key, leaf_value)
+            }
+            (Some((leaf_key, leaf_value)), None) => {
+                // The leaf of another key must be the only one in the subtree
+                // where `key` would have been
+                if leaf_key == key
+                    || (0..self.siblings.len()).any(|depth| bit(leaf_key, depth) != bit(key, depth))
+                {
+                    return false;
+                }
+                SparseMerkleTree::hash_leaf(leaf_key, leaf_value)
+            }
+            (None, Some(_)) => return false,
+            (None, None) => EMPTY_HASH,
+        };
+
+        for (depth, sibling) in self.siblings.iter().enumerate().rev() {
+            current = if bit(key, depth) {
+                SparseMerkleTree::hash_nodes(sibling, &current)
+            } else {
+                SparseMerkleTree::hash_nodes(&current, sibling)
+            };
+        }
+
+        current == *root
+    }
+
+    /// Compact encoding of the proof:
+    /// - 1 byte: 1 if a leaf follows, 0 otherwise
+    /// - 64 bytes: the leaf key and account hash, if any
+    /// - 2 bytes: number of siblings, little endian
+    /// - one bit per sibling, set if it is not empty, packed in bytes
+    /// - 32 bytes per non empty sibling
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        match &self.leaf {
+            Some((key, value)) => {
+                bytes.push(1);
+                bytes.extend_from_slice(key.as_ref());
+                bytes.extend_from_slice(value.as_ref());
+            }
+            None => bytes.push(0),
+        }
+
+        bytes.extend_from_slice(&(self.siblings.len() as u16).to_le_bytes());
+        let mut bitmap = vec![0u8; self.siblings.len().div_ceil(8)];
+        for (i, sibling) in self.siblings.iter().enumerate() {
+            if *sibling != EMPTY_HASH {
+                bitmap[i / 8] |= 1 << (i % 8);
+            }
+        }
+        bytes.extend_from_slice(&bitmap);
+        for sibling in self
+            .siblings
+            .iter()
+            .filter(|sibling| **sibling != EMPTY_HASH)
+        {
+            bytes.extend_from_slice(sibling.as_ref());
+        }
+
+        bytes
+    }
+
+    /// Decodes a proof encoded with [SparseMerkleProof::to_bytes], returning
+    /// none if the bytes are malformed
+    pub fn try_from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (has_leaf, mut bytes) = bytes.split_first()?;
+        let leaf = match has_leaf {
+            0 => None,
+            1 => {
+                let (key, rest) = split_array::<32>(bytes)?;
+                let (value, rest) = split_array::<32>(rest)?;
+                bytes = rest;
+                Some((Pubkey::new_from_array(key), Hash::new_from_array(value)))
+            }
+            _ => return None,
+        };
+
+        let (count, rest) = split_array::<2>(bytes)?;
+        let count = u16::from_le_bytes(count) as usize;
+        if count > KEY_BITS {
+            return None;
+        }
+        let bitmap_len = count.div_ceil(8);
+        if rest.len() < bitmap_len {
+            return None;
+        }
+        let (bitmap, mut rest) = rest.split_at(bitmap_len);
+
+        let mut siblings = Vec::with_capacity(count);
+        for i in 0..count {
+            if bitmap[i / 8] & (1 << (i % 8)) != 0 {
+                let (sibling, remaining) = split_array::<32>(rest)?;
+                rest = remaining;
+                siblings.push(Hash::new_from_array(sibling));
+            } else {
+                siblings.push(EMPTY_HASH);
+            }
+        }
+
+        if !rest.is_empty() {
+            return None;
+        }
+
+        Some(Self { leaf, siblings })
+    }
+}
+
+fn split_array<const N: usize>(bytes: &[u8]) -> Option<([u8; N], &[u8])> {
+    if bytes.len() < N {
+        return None;
+    }
+    let (array, rest) = bytes.split_at(N);
+    Some((array.try_into().ok()?, rest))
+}
+
+/// Verifies that `account` is stored at `pubkey` in the tree of the given
+/// `root`, or that nothing is when `account` is none
+pub fn verify_proof(
+    root: &Hash,
+    pubkey: &Pubkey,
+    account: Option<&Account>,
+    proof: &SparseMerkleProof,
+) -> bool {
+    let value = account.map(SparseMerkleTree::hash_account);
+    proof.verify(root, pubkey, value.as_ref())
+}
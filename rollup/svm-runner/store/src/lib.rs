@@ -0,0 +1,548 @@
+//! Persistent account store for the rollup state.
+//!
+//! Every batch is written as a record holding its [ExecutionInput] without the
+//! accounts, the resulting state root and the accounts it changed. Full snapshots of the
+//! state are taken every `snapshot_interval` batches, so that the state at any
+//! height is the closest snapshot below it with the following records applied.
+//!
+//! Files are written to a temporary path and renamed into place, so a batch is
+//! either entirely committed or not at all.
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_sdk::{
+    account::{AccountSharedData, ReadableAccount},
+    hash::Hash,
+    native_loader,
+    pubkey::Pubkey,
+};
+use solana_svm::transaction_processing_callback::TransactionProcessingCallback;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, File},
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+use svm_runner_types::{hash_state, ExecutionInput, RollupState, SerializableAccount, State};
+use thiserror::Error;
+
+const SNAPSHOT_PREFIX: &str = "snapshot-";
+const BATCH_PREFIX: &str = "batch-";
+const FILE_EXTENSION: &str = "bin";
+
+#[derive(Error, Debug)]
+pub enum StoreError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Unknown state root {0}")]
+    UnknownStateRoot(Hash),
+    #[error("No batch at height {0}")]
+    MissingBatch(u64),
+    #[error("Corrupted store: {0}")]
+    Corrupted(String),
+}
+
+pub type Result<T> = std::result::Result<T, StoreError>;
+
+/// A batch as committed to the store
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone)]
+pub struct BatchRecord {
+    pub height: u64,
+    /// Input the batch was executed with, without its accounts, which are the
+    /// state at the previous height
+    pub input: ExecutionInput,
+    pub state_root: Hash,
+    /// Accounts changed by the batch, none if they were removed
+    pub updates: Vec<(Pubkey, Option<SerializableAccount>)>,
+}
+
+/// Full state of the rollup at a height
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone)]
+struct Snapshot {
+    height: u64,
+    state_root: Hash,
+    state: RollupState,
+}
+
+pub struct AccountStore {
+    path: PathBuf,
+    snapshot_interval: u64,
+    /// State at the head of the store
+    accounts: RwLock<BTreeMap<Pubkey, SerializableAccount>>,
+    /// State root at every height, the last one being the head
+    state_roots: RwLock<BTreeMap<u64, Hash>>,
+    /// Builtin program accounts, which are not part of the rollup state
+    builtins: RwLock<HashMap<Pubkey, AccountSharedData>>,
+}
+
+impl AccountStore {
+    /// Opens the store at `path`, creating it with the `genesis` state at
+    /// height 0 if it does not exist yet
+    pub fn open(
+        path: impl AsRef<Path>,
+        genesis: RollupState,
+        snapshot_interval: u64,
+    ) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        fs::create_dir_all(&path)?;
+
+        let store = Self {
+            path,
+            snapshot_interval: snapshot_interval.max(1),
+            accounts: RwLock::default(),
+            state_roots: RwLock::default(),
+            builtins: RwLock::default(),
+        };
+
+        if store.heights(SNAPSHOT_PREFIX)?.is_empty() {
+            let state_root = hash_state(genesis.clone());
+            store.write_file(
+                SNAPSHOT_PREFIX,
+                0,
+                &Snapshot {
+                    height: 0,
+                    state_root,
+                    state: genesis,
+                },
+            )?;
+        }
+
+        store.load()?;
+        Ok(store)
+    }
+
+    /// Height and state root of the last committed batch
+    pub fn head(&self) -> (u64, Hash) {
+        let state_roots = self.state_roots.read().unwrap();
+        let (height, state_root) = state_roots
+            .last_key_value()
+            .expect("genesis is always stored");
+        (*height, *state_root)
+    }
+
+    /// State root at every height, from genesis to the head
+    pub fn state_roots(&self) -> Vec<(u64, Hash)> {
+        self.state_roots
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(height, state_root)| (*height, *state_root))
+            .collect()
+    }
+
+    pub fn get_account(&self, pubkey: &Pubkey) -> Option<SerializableAccount> {
+        self.accounts.read().unwrap().get(pubkey).cloned()
+    }
+
+    /// State at the head of the store, sorted by pubkey
+    pub fn rollup_state(&self) -> RollupState {
+        to_rollup_state(&self.accounts.read().unwrap())
+    }
+
+    /// Commits the batch executed with `input` on the head state, which wrote
+    /// the accounts in `updates`, returning its height
+    pub fn commit_batch(
+        &self,
+        mut input: ExecutionInput,
+        updates: Vec<(Pubkey, Option<SerializableAccount>)>,
+    ) -> Result<u64> {
+        let mut accounts = self.accounts.write().unwrap();
+        let (height, _) = self.head();
+        let height = height + 1;
+
+        // Accounts written with their current value are not recorded
+        let updates = updates
+            .into_iter()
+            .filter(|(pubkey, account)| accounts.get(pubkey) != account.as_ref())
+            .collect::<Vec<_>>();
+        let previous = updates
+            .iter()
+            .map(|(pubkey, _)| (*pubkey, accounts.get(pubkey).cloned()))
+            .collect::<Vec<_>>();
+        apply_updates(&mut accounts, updates.clone());
+
+        let state_root = hash_state(to_rollup_state(&accounts));
+        // The accounts are the state at the previous height, rebuilt on read
+        input.accounts = RollupState { states: vec![] };
+        let record = BatchRecord {
+            height,
+            input,
+            state_root,
+            updates,
+        };
+        if let Err(err) = self.write_file(BATCH_PREFIX, height, &record) {
+            apply_updates(&mut accounts, previous);
+            return Err(err);
+        }
+
+        self.state_roots.write().unwrap().insert(height, state_root);
+
+        if height % self.snapshot_interval == 0 {
+            self.write_file(
+                SNAPSHOT_PREFIX,
+                height,
+                &Snapshot {
+                    height,
+                    state_root,
+                    state: to_rollup_state(&accounts),
+                },
+            )?;
+        }
+
+        Ok(height)
+    }
+
+    /// Record of the batch committed at `height`
+    pub fn batch(&self, height: u64) -> Result<BatchRecord> {
+        self.read_file(BATCH_PREFIX, height)?
+            .ok_or(StoreError::MissingBatch(height))
+    }
+
+    /// Input the batch at `height` was executed with, from which it can be
+    /// proven again. Genesis is not a batch and has no input.
+    pub fn execution_input(&self, height: u64) -> Result<ExecutionInput> {
+        let previous_height = height
+            .checked_sub(1)
+            .ok_or(StoreError::MissingBatch(height))?;
+        let mut input = self.batch(height)?.input;
+        input.accounts = self.state_at(previous_height)?;
+        Ok(input)
+    }
+
+    /// State after the batch at `height`
+    pub fn state_at(&self, height: u64) -> Result<RollupState> {
+        let snapshot_height = self
+            .heights(SNAPSHOT_PREFIX)?
+            .into_iter()
+            .filter(|snapshot_height| *snapshot_height <= height)
+            .max()
+            .ok_or_else(|| StoreError::Corrupted("missing genesis snapshot".to_string()))?;
+        let snapshot: Snapshot = self
+            .read_file(SNAPSHOT_PREFIX, snapshot_height)?
+            .ok_or_else(|| StoreError::Corrupted(format!("missing snapshot {snapshot_height}")))?;
+
+        let mut accounts = snapshot
+            .state
+            .states
+            .into_iter()
+            .map(|state| (state.pubkey, state.account))
+            .collect::<BTreeMap<_, _>>();
+        for batch_height in snapshot_height + 1..=height {
+            apply_updates(&mut accounts, self.batch(batch_height)?.updates);
+        }
+
+        Ok(to_rollup_state(&accounts))
+    }
+
+    /// Discards every batch committed after the one resulting in
+    /// `state_root`, returning the new head height
+    pub fn rollback_to(&self, state_root: &Hash) -> Result<u64> {
+        let height = self
+            .state_roots
+            .read()
+            .unwrap()
+            .iter()
+            .rev()
+            .find(|(_, root)| *root == state_root)
+            .map(|(height, _)| *height)
+            .ok_or(StoreError::UnknownStateRoot(*state_root))?;
+
+        let state = self.state_at(height)?;
+        let mut accounts = self.accounts.write().unwrap();
+
+        // Highest files first, so that an interrupted rollback leaves a valid store
+        let mut discarded = self
+            .heights(BATCH_PREFIX)?
+            .into_iter()
+            .filter(|batch_height| *batch_height > height)
+            .map(|batch_height| (batch_height, BATCH_PREFIX))
+            .chain(
+                self.heights(SNAPSHOT_PREFIX)?
+                    .into_iter()
+                    .filter(|snapshot_height| *snapshot_height > height)
+                    .map(|snapshot_height| (snapshot_height, SNAPSHOT_PREFIX)),
+            )
+            .collect::<Vec<_>>();
+        discarded.sort_by(|a, b| b.cmp(a));
+        for (discarded_height, prefix) in discarded {
+            fs::remove_file(self.file_path(prefix, discarded_height))?;
+        }
+
+        *accounts = state
+            .states
+            .into_iter()
+            .map(|state| (state.pubkey, state.account))
+            .collect();
+        self.state_roots
+            .write()
+            .unwrap()
+            .retain(|root_height, _| *root_height <= height);
+
+        Ok(height)
+    }
+
+    /// Loads the head state from the last snapshot and the following batches
+    fn load(&self) -> Result<()> {
+        let mut state_roots = BTreeMap::new();
+        for height in self.heights(SNAPSHOT_PREFIX)? {
+            let snapshot: Snapshot = self
+                .read_file(SNAPSHOT_PREFIX, height)?
+                .ok_or_else(|| StoreError::Corrupted(format!("missing snapshot {height}")))?;
+            state_roots.insert(snapshot.height, snapshot.state_root);
+        }
+        let batch_heights = self.heights(BATCH_PREFIX)?;
+        for height in batch_heights.iter() {
+            state_roots.insert(*height, self.batch(*height)?.state_root);
+        }
+
+        let head = *state_roots
+            .keys()
+            .last()
+            .ok_or_else(|| StoreError::Corrupted("missing genesis snapshot".to_string()))?;
+        if let Some(missing) = (1..=head).find(|height| !state_roots.contains_key(height)) {
+            return Err(StoreError::MissingBatch(missing));
+        }
+
+        let state = self.state_at(head)?;
+        *self.accounts.write().unwrap() = state
+            .states
+            .into_iter()
+            .map(|state| (state.pubkey, state.account))
+            .collect();
+        *self.state_roots.write().unwrap() = state_roots;
+
+        Ok(())
+    }
+
+    fn file_path(&self, prefix: &str, height: u64) -> PathBuf {
+        self.path
+            .join(format!("{prefix}{height:020}.{FILE_EXTENSION}"))
+    }
+
+    /// Heights of the files with the given prefix, sorted
+    fn heights(&self, prefix: &str) -> Result<Vec<u64>> {
+        let mut heights = fs::read_dir(&self.path)?
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name().into_string().ok()?;
+                name.strip_prefix(prefix)?
+                    .strip_suffix(&format!(".{FILE_EXTENSION}"))?
+                    .parse::<u64>()
+                    .ok()
+            })
+            .collect::<Vec<_>>();
+        heights.sort_unstable();
+        Ok(heights)
+    }
+
+    /// Writes the file atomically, by renaming it into place once synced
+    fn write_file(&self, prefix: &str, height: u64, value: &impl BorshSerialize) -> Result<()> {
+        let path = self.file_path(prefix, height);
+        let tmp_path = path.with_extension("tmp");
+
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        borsh::to_writer(&mut writer, value)?;
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+        drop(writer);
+
+        fs::rename(&tmp_path, &path)?;
+        File::open(&self.path)?.sync_all()?;
+        Ok(())
+    }
+
+    fn read_file<T: BorshDeserialize>(&self, prefix: &str, height: u64) -> Result<Option<T>> {
+        let path = self.file_path(prefix, height);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let mut reader = BufReader::new(File::open(path)?);
+        Ok(Some(borsh::from_reader(&mut reader)?))
+    }
+}
+
+impl TransactionProcessingCallback for AccountStore {
+    fn account_matches_owners(&self, account: &Pubkey, owners: &[Pubkey]) -> Option<usize> {
+        self.get_account_shared_data(account)
+            .filter(|account| account.lamports() != 0)
+            .and_then(|account| owners.iter().position(|entry| account.owner() == entry))
+    }
+
+    fn get_account_shared_data(&self, pubkey: &Pubkey) -> Option<AccountSharedData> {
+        self.get_account(pubkey)
+            .map(AccountSharedData::from)
+            .or_else(|| self.builtins.read().unwrap().get(pubkey).cloned())
+    }
+
+    fn add_builtin_account(&self, name: &str, program_id: &Pubkey) {
+        let account_data = native_loader::create_loadable_account_with_fields(name, (5000, 0));
+        self.builtins
+            .write()
+            .unwrap()
+            .insert(*program_id, account_data);
+    }
+}
+
+fn apply_updates(
+    accounts: &mut BTreeMap<Pubkey, SerializableAccount>,
+    updates: Vec<(Pubkey, Option<SerializableAccount>)>,
+) {
+    for (pubkey, account) in updates {
+        match account {
+            Some(account) => accounts.insert(pubkey, account),
+            None => accounts.remove(&pubkey),
+        };
+    }
+}
+
+fn to_rollup_state(accounts: &BTreeMap<Pubkey, SerializableAccount>) -> RollupState {
+    RollupState {
+        states: accounts
+            .iter()
+            .map(|(pubkey, account)| State {
+                pubkey: *pubkey,
+                account: account.clone(),
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::system_program;
+    use svm_runner_types::{BlockHeader, FeeConfig};
+
+    fn temp_store_path(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("svm-runner-store-{name}-{}", Pubkey::new_unique()));
+        let _ = fs::remove_dir_all(&path);
+        path
+    }
+
+    fn state(balances: &[(Pubkey, u64)]) -> RollupState {
+        RollupState {
+            states: balances
+                .iter()
+                .map(|(pubkey, lamports)| State {
+                    pubkey: *pubkey,
+                    account: SerializableAccount {
+                        lamports: *lamports,
+                        owner: system_program::id(),
+                        ..SerializableAccount::default()
+                    },
+                })
+                .collect(),
+        }
+    }
+
+    /// Updates setting the balances, removing the accounts without lamports
+    fn updates(balances: &[(Pubkey, u64)]) -> Vec<(Pubkey, Option<SerializableAccount>)> {
+        state(balances)
+            .states
+            .into_iter()
+            .map(|state| {
+                let account = Some(state.account).filter(|account| account.lamports != 0);
+                (state.pubkey, account)
+            })
+            .collect()
+    }
+
+    fn input() -> ExecutionInput {
+        ExecutionInput {
+            accounts: RollupState { states: vec![] },
+            txs: vec![],
+            ramp_txs: vec![],
+            forced_txs: vec![],
+            forced_txs_start: 0,
+            forced_txs_prior_hash: Hash::default(),
+            header: BlockHeader::default(),
+            fees: FeeConfig::default(),
+            batch_number: 0,
+        }
+    }
+
+    #[test]
+    fn test_commit_and_reopen() {
+        let path = temp_store_path("reopen");
+        let alice = Pubkey::new_unique();
+        let bob = Pubkey::new_unique();
+
+        let store = AccountStore::open(&path, state(&[(alice, 10)]), 2).unwrap();
+        assert_eq!(store.head().0, 0);
+
+        for height in 1..=3 {
+            let input = ExecutionInput {
+                accounts: store.rollup_state(),
+                ..input()
+            };
+            let committed = store
+                .commit_batch(input, updates(&[(alice, 10 - height), (bob, height)]))
+                .unwrap();
+            assert_eq!(committed, height);
+        }
+        let (height, state_root) = store.head();
+        drop(store);
+
+        let store = AccountStore::open(&path, RollupState { states: vec![] }, 2).unwrap();
+        assert_eq!(store.head(), (height, state_root));
+        assert_eq!(store.get_account(&alice).unwrap().lamports, 7);
+        assert_eq!(store.get_account(&bob).unwrap().lamports, 3);
+        // Batches are executed against the store through the callback
+        assert_eq!(store.get_account_shared_data(&bob).unwrap().lamports(), 3);
+        assert_eq!(
+            store.account_matches_owners(&alice, &[Pubkey::new_unique(), system_program::id()]),
+            Some(1)
+        );
+        assert_eq!(hash_state(store.state_at(3).unwrap()), state_root);
+
+        // Inputs are stored without their accounts, which are rebuilt
+        assert!(store.batch(3).unwrap().input.accounts.states.is_empty());
+        assert_eq!(
+            hash_state(store.execution_input(3).unwrap().accounts),
+            hash_state(store.state_at(2).unwrap())
+        );
+        assert!(matches!(
+            store.execution_input(0),
+            Err(StoreError::MissingBatch(0))
+        ));
+
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn test_state_at_and_rollback() {
+        let path = temp_store_path("rollback");
+        let alice = Pubkey::new_unique();
+        let bob = Pubkey::new_unique();
+
+        let store = AccountStore::open(&path, state(&[(alice, 10)]), 2).unwrap();
+        store
+            .commit_batch(input(), updates(&[(alice, 5), (bob, 5)]))
+            .unwrap();
+        let first_root = store.head().1;
+        assert_eq!(first_root, hash_state(state(&[(alice, 5), (bob, 5)])));
+        store
+            .commit_batch(input(), updates(&[(alice, 0), (bob, 10)]))
+            .unwrap();
+        store.commit_batch(input(), updates(&[(bob, 1)])).unwrap();
+
+        // Historical states are rebuilt from the snapshots and batches
+        assert_eq!(hash_state(store.state_at(1).unwrap()), first_root);
+        assert!(store.get_account(&alice).is_none());
+
+        assert_eq!(store.rollback_to(&first_root).unwrap(), 1);
+        assert_eq!(store.head(), (1, first_root));
+        assert_eq!(store.get_account(&alice).unwrap().lamports, 5);
+        assert!(matches!(store.batch(2), Err(StoreError::MissingBatch(2))));
+
+        // The rollback is persisted
+        drop(store);
+        let store = AccountStore::open(&path, RollupState { states: vec![] }, 2).unwrap();
+        assert_eq!(store.head(), (1, first_root));
+        assert!(matches!(
+            store.rollback_to(&Hash::new_unique()),
+            Err(StoreError::UnknownStateRoot(_))
+        ));
+
+        fs::remove_dir_all(path).unwrap();
+    }
+}
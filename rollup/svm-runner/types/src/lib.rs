@@ -1,39 +1,250 @@
 use borsh::{BorshDeserialize, BorshSerialize};
+use merkle_tree::SparseMerkleTree;
 use serde::{Deserialize, Serialize};
 use solana_account::{Account, AccountSharedData, ReadableAccount};
-use solana_program::{clock::Epoch, hash::Hash};
+use solana_program::{clock::Epoch, hash::Hash, pubkey};
 use solana_pubkey::Pubkey;
 use solana_sha256_hasher::hashv;
 
+mod receipt;
+mod status;
+pub use receipt::*;
+pub use status::*;
+
 #[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone)]
 pub struct RampTx {
     pub is_onramp: bool,
     pub user: Pubkey,
+    /// Lamports moved in or out of the rollup
+    pub amount: u64,
+    /// Tokens moved along with the lamports, if any
+    pub token: Option<TokenAmount>,
+}
+
+/// Amount of an L1 SPL token, mirrored on L2 by the mint at
+/// [mirrored_mint_address]
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct TokenAmount {
+    /// Mint of the token on L1
+    pub mint: Pubkey,
+    pub decimals: u8,
     pub amount: u64,
 }
 
 pub type ExecutionOutput = Hash;
 
+/// Owner of the withdrawal receipts. No program is deployed at this address
+/// and receipt addresses have no private key, so receipts can never be debited.
+pub const WITHDRAWAL_RECEIPT_OWNER: Pubkey = pubkey!("WithdrawReceipt1111111111111111111111111111");
+
+const WITHDRAWAL_RECEIPT_SEED: &[u8] = b"withdrawal:";
+
+/// Data of the account written to the rollup state for every applied off-ramp.
+/// It holds the withdrawn lamports, which the user claims on L1 by proving the
+/// account against the state root. The tokens of on-ramps that could not be
+/// applied are refunded with a receipt as well.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct WithdrawalReceipt {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub token: Option<TokenAmount>,
+    /// The receipt address is derived from these, which the bridge checks so
+    /// that accounts created by transactions can't pass as receipts
+    pub prior_state_root: Hash,
+    pub index: u64,
+}
+
+/// Address of the receipt of the `index`-th ramp tx of the batch executed on
+/// the state of root `prior_state_root`, unique across batches
+pub fn withdrawal_receipt_address(prior_state_root: &Hash, index: u64) -> Pubkey {
+    Pubkey::new_from_array(
+        hashv(&[
+            WITHDRAWAL_RECEIPT_SEED,
+            prior_state_root.as_ref(),
+            &index.to_le_bytes(),
+        ])
+        .to_bytes(),
+    )
+}
+
+/// Mint authority of the mirrored mints. It has no private key, so mirrored
+/// tokens are only minted by on-ramps.
+pub const MIRRORED_MINT_AUTHORITY: Pubkey = pubkey!("MirroredMintAuthority1111111111111111111111");
+
+const MIRRORED_MINT_SEED: &[u8] = b"mirror:";
+
+/// Address of the SPL Token mint mirroring the L1 `mint` on the rollup
+pub fn mirrored_mint_address(mint: &Pubkey) -> Pubkey {
+    Pubkey::new_from_array(hashv(&[MIRRORED_MINT_SEED, mint.as_ref()]).to_bytes())
+}
+
+/// Next link of the ramp txs hash chain, `sha256(previous || borsh(ramp_tx))`.
+/// The bridge extends the same chain every time a ramp tx is queued.
+pub fn chain_ramp_tx(previous: &Hash, ramp_tx: &RampTx) -> Hash {
+    hashv(&[previous.as_ref(), &borsh::to_vec(ramp_tx).unwrap()])
+}
+
+/// Hash chain over `ramp_txs`, starting from the zero hash
+pub fn hash_ramp_txs<'a>(ramp_txs: impl IntoIterator<Item = &'a RampTx>) -> Hash {
+    ramp_txs.into_iter().fold(Hash::default(), |hash, ramp_tx| {
+        chain_ramp_tx(&hash, ramp_tx)
+    })
+}
+
+/// Next link of the forced txs hash chain, `sha256(previous || tx)`, `tx`
+/// being the bytes submitted to the bridge. The bridge extends the same chain
+/// every time a transaction is forced, from the zero hash.
+pub fn chain_forced_tx(previous: &Hash, tx: &[u8]) -> Hash {
+    hashv(&[previous.as_ref(), tx])
+}
+
+/// Hash chain over `forced_txs`, extending `prior_hash`, the value of the
+/// chain before the first of them
+pub fn hash_forced_txs<'a>(
+    prior_hash: &Hash,
+    forced_txs: impl IntoIterator<Item = &'a Vec<u8>>,
+) -> Hash {
+    forced_txs
+        .into_iter()
+        .fold(*prior_hash, |hash, tx| chain_forced_tx(&hash, tx))
+}
+
+/// Root of the [SparseMerkleTree] holding the states, which does not depend on
+/// their order and against which single accounts can be proven.
 pub fn hash_state(output: RollupState) -> Hash {
-    let mut data = Vec::new();
-    for state in output.states.iter() {
-        data.extend_from_slice(state.pubkey.as_ref());
-        data.extend_from_slice(&bincode::serialize(&state.account).unwrap());
-    }
-    hashv(&[data.as_slice()])
+    let accounts = output
+        .states
+        .into_iter()
+        .map(|state| (state.pubkey, Account::from(state.account)))
+        .collect::<Vec<_>>();
+
+    let mut tree = SparseMerkleTree::new();
+    tree.update(
+        accounts
+            .iter()
+            .map(|(pubkey, account)| (*pubkey, Some(account))),
+    );
+    tree.get_root()
 }
 
+/// Hash of the serialized transactions of a batch
+pub fn hash_txs(txs: &[u8]) -> Hash {
+    hashv(&[txs])
+}
+
+/// Hash of whether each ramp tx of a batch was applied
+pub fn hash_ramp_results(ramp_results: &[bool]) -> Hash {
+    hashv(&[&borsh::to_vec(ramp_results).unwrap()])
+}
+
+/// Hash of the block context of a batch, `sha256(borsh(header) || borsh(fees))`
+pub fn hash_header(header: &BlockHeader, fees: &FeeConfig) -> Hash {
+    hashv(&[
+        &borsh::to_vec(header).unwrap(),
+        &borsh::to_vec(fees).unwrap(),
+    ])
+}
+
+/// Public values of the proof of a batch. They only hold fixed-size
+/// commitments, so that the proof submitted to the bridge has the same size
+/// whatever the batch. The data they commit to is published as [BatchData].
 #[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone)]
 pub struct CommitedValues {
-    pub input: ExecutionInput,
+    /// Root of the state the batch was executed on, see [hash_state]. The
+    /// bridge requires it to be the root of the last proven batch.
+    pub prior_state_root: Hash,
+    /// See [ExecutionInput::batch_number]
+    pub batch_number: u64,
+    /// Root of the resulting state
     pub output: ExecutionOutput,
+    /// See [hash_txs]
+    pub txs_hash: Hash,
+    /// Hash chain over the ramp txs, see [hash_ramp_txs]
+    pub ramp_txs_hash: Hash,
+    /// See [ExecutionInput::forced_txs_start]
+    pub forced_txs_start: u64,
+    /// Number of forced txs executed by the batch
+    pub forced_txs_count: u64,
+    /// Hash chain over every forced tx up to the last one executed by the
+    /// batch, see [hash_forced_txs]. The bridge checks it against the value it
+    /// recorded when that transaction was forced.
+    pub forced_txs_hash: Hash,
+    /// See [hash_ramp_results]
+    pub ramp_results_hash: Hash,
+    /// See [hash_header]
+    pub header_hash: Hash,
+    /// Root of the receipts of the transactions, see [hash_receipts]
+    pub receipts_root: Hash,
+}
+
+/// Data of a proven batch, published alongside its proof
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone)]
+pub struct BatchData {
+    pub input: ExecutionInput,
+    /// Whether each of `input.ramp_txs` was applied to the rollup state
+    pub ramp_results: Vec<bool>,
+}
+
+impl BatchData {
+    /// Checks that the data matches the commitments of `values`. The resulting
+    /// state and receipts are only checked by executing the batch again.
+    pub fn matches(&self, values: &CommitedValues) -> bool {
+        values.prior_state_root == hash_state(self.input.accounts.clone())
+            && values.batch_number == self.input.batch_number
+            && values.txs_hash == hash_txs(&self.input.txs)
+            && values.ramp_txs_hash == hash_ramp_txs(&self.input.ramp_txs)
+            && values.forced_txs_start == self.input.forced_txs_start
+            && values.forced_txs_count == self.input.forced_txs.len() as u64
+            && values.forced_txs_hash
+                == hash_forced_txs(&self.input.forced_txs_prior_hash, &self.input.forced_txs)
+            && values.ramp_results_hash == hash_ramp_results(&self.ramp_results)
+            && values.header_hash == hash_header(&self.input.header, &self.input.fees)
+    }
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone)]
 pub struct ExecutionInput {
     pub accounts: RollupState, // use Vec<State> instead
-    pub txs: Vec<u8>,          // Vec of serialized transactions: Vec<Transaction>
+    pub txs: Vec<u8>,          // Vec of serialized transactions: Vec<VersionedTransaction>
     pub ramp_txs: Vec<RampTx>,
+    /// Transactions submitted on L1 to the bridge, as bincode serialized
+    /// `VersionedTransaction`s, executed before `txs`. They are the ones queued
+    /// from `forced_txs_start` on, the bridge requiring a proof to execute the
+    /// ones queued long enough ago. Bytes that can't be deserialized are
+    /// rejected like invalid transactions.
+    pub forced_txs: Vec<Vec<u8>>,
+    /// Index on the bridge of the first of `forced_txs`
+    pub forced_txs_start: u64,
+    /// Value of the forced txs hash chain before `forced_txs_start`
+    pub forced_txs_prior_hash: Hash,
+    pub header: BlockHeader,
+    pub fees: FeeConfig,
+    /// Number of the batch, the first batch after genesis being 1. The bridge
+    /// only accepts the proof of the batch following the last proven one.
+    pub batch_number: u64,
+}
+
+/// Fees charged to the transactions of a batch
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone, Default)]
+pub struct FeeConfig {
+    /// Base fee per signature. Priority fees requested with compute budget
+    /// instructions are charged on top of it.
+    pub lamports_per_signature: u64,
+    /// Account credited with every fee paid in the batch
+    pub collector: Pubkey,
+}
+
+/// Block context the batch is executed in, supplied by the sequencer. It fills
+/// the `Clock` sysvar, while the `RecentBlockhashes` and `SlotHashes` sysvars
+/// are read from the [RollupStatus] in the state.
+#[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone, Default)]
+pub struct BlockHeader {
+    /// Slot the batch is executed at. Programs deployed or upgraded in a batch
+    /// can only be invoked from the next slot on, as on Solana.
+    pub slot: u64,
+    /// Time of the batch, bounded by the bridge against L1 time
+    pub unix_timestamp: i64,
+    pub epoch: u64,
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Deserialize, Serialize, Debug, Clone)]
@@ -111,4 +322,4 @@ impl From<SerializableAccount> for AccountSharedData {
 pub struct SP1Groth16Proof {
     pub proof: Vec<u8>,
     pub sp1_public_inputs: CommitedValues,
-}
\ No newline at end of file
+}
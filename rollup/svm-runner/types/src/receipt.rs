@@ -0,0 +1,61 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use merkle_tree::{hash_list_leaf, list_root};
+use solana_program::hash::Hash;
+use solana_pubkey::Pubkey;
+
+/// Outcome of a transaction of the batch. The receipts of a batch are committed
+/// with [hash_receipts], so that the success of a single transaction can be
+/// proven against the root with a list proof.
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct TransactionReceipt {
+    pub signature: [u8; 64],
+    /// Bincode serialized `TransactionError`, as in the transaction status
+    /// of Solana, or `None` if the transaction succeeded
+    pub error: Option<Vec<u8>>,
+    /// Fee charged to the fee payer, even if the transaction failed
+    pub fee: u64,
+    pub compute_units_consumed: u64,
+    pub log_messages: Vec<String>,
+    pub return_data: Option<ReturnData>,
+    /// Instructions invoked by each instruction of the message
+    pub inner_instructions: Vec<Vec<InnerInstruction>>,
+}
+
+impl TransactionReceipt {
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Leaf of the receipt in the receipts tree
+    pub fn hash(&self) -> Hash {
+        hash_list_leaf(&borsh::to_vec(self).unwrap())
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct ReturnData {
+    pub program_id: Pubkey,
+    pub data: Vec<u8>,
+}
+
+/// Instruction invoked through CPI, its accounts indexing the account keys of
+/// the message
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, PartialEq, Eq)]
+pub struct InnerInstruction {
+    pub program_id_index: u8,
+    pub accounts: Vec<u8>,
+    pub data: Vec<u8>,
+    /// Invocation stack height, the top level instructions being at 1
+    pub stack_height: u8,
+}
+
+/// Root of the Merkle tree over the receipts of a batch, in the order of the
+/// transactions
+pub fn hash_receipts(receipts: &[TransactionReceipt]) -> Hash {
+    list_root(
+        &receipts
+            .iter()
+            .map(TransactionReceipt::hash)
+            .collect::<Vec<_>>(),
+    )
+}
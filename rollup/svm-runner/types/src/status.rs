@@ -0,0 +1,111 @@
+//! Recent blockhashes and processed signatures of the rollup, kept in the rollup
+//! state so that every batch is checked against the ones before it
+use crate::SerializableAccount;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{hash::Hash, pubkey, sysvar};
+use solana_pubkey::Pubkey;
+use solana_sha256_hasher::hashv;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Address of the account holding the [RollupStatus]. It is owned by the
+/// sysvar program and only written by the runner, between batches.
+pub const ROLLUP_STATUS_ADDRESS: Pubkey = pubkey!("SysvarStatusCache11111111111111111111111111");
+
+/// Number of batches a blockhash can be referenced by transactions for
+pub const MAX_RECENT_BLOCKHASHES: usize = 150;
+
+/// Blockhash queue and signature status cache of the rollup
+#[derive(BorshDeserialize, BorshSerialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct RollupStatus {
+    /// Slot and blockhash of the last batches, newest first
+    pub blockhashes: Vec<(u64, Hash)>,
+    /// Signatures of the processed transactions, by the blockhash they
+    /// reference. They are forgotten once the blockhash expires, as the
+    /// transactions can't be processed anymore.
+    pub signatures: BTreeMap<Hash, BTreeSet<[u8; 64]>>,
+}
+
+impl RollupStatus {
+    /// Status before the first batch, whose only blockhash is derived from the
+    /// genesis state root
+    pub fn genesis(state_root: &Hash) -> Self {
+        Self {
+            blockhashes: vec![(0, hashv(&[state_root.as_ref()]))],
+            signatures: BTreeMap::new(),
+        }
+    }
+
+    /// Reads the status from its account in the state of root `state_root`,
+    /// which has none before the first batch
+    pub fn from_account(
+        account: Option<&SerializableAccount>,
+        state_root: &Hash,
+    ) -> std::io::Result<Self> {
+        match account {
+            Some(account) => Self::try_from_slice(&account.data),
+            None => Ok(Self::genesis(state_root)),
+        }
+    }
+
+    /// Blockhash produced by the last batch, referenced by new transactions
+    pub fn latest_blockhash(&self) -> Hash {
+        self.blockhashes
+            .first()
+            .map(|(_, blockhash)| *blockhash)
+            .unwrap_or_default()
+    }
+
+    pub fn is_blockhash_valid(&self, blockhash: &Hash) -> bool {
+        self.blockhashes
+            .iter()
+            .any(|(_, recent_blockhash)| recent_blockhash == blockhash)
+    }
+
+    pub fn is_processed(&self, signature: &[u8; 64]) -> bool {
+        self.signatures
+            .values()
+            .any(|signatures| signatures.contains(signature))
+    }
+
+    pub fn record(&mut self, blockhash: Hash, signature: [u8; 64]) {
+        self.signatures
+            .entry(blockhash)
+            .or_default()
+            .insert(signature);
+    }
+
+    /// Registers the blockhash of the batch executed at `slot`, expiring the
+    /// oldest one and the signatures referencing it
+    pub fn advance(&mut self, slot: u64, blockhash: Hash) {
+        self.blockhashes.insert(0, (slot, blockhash));
+        self.blockhashes.truncate(MAX_RECENT_BLOCKHASHES);
+
+        let blockhashes = &self.blockhashes;
+        self.signatures.retain(|blockhash, _| {
+            blockhashes
+                .iter()
+                .any(|(_, recent_blockhash)| recent_blockhash == blockhash)
+        });
+    }
+
+    /// Account holding the status, keeping the lamports of the current one
+    pub fn to_account(&self, lamports: u64) -> SerializableAccount {
+        SerializableAccount {
+            lamports,
+            data: borsh::to_vec(self).unwrap(),
+            owner: sysvar::id(),
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+}
+
+/// Blockhash of the batch executed at `slot` on the state of root
+/// `prior_state_root`, chained to the blockhash of the previous batch
+pub fn next_blockhash(previous: &Hash, prior_state_root: &Hash, slot: u64) -> Hash {
+    hashv(&[
+        previous.as_ref(),
+        prior_state_root.as_ref(),
+        &slot.to_le_bytes(),
+    ])
+}
@@ -5,7 +5,10 @@ sp1_zkvm::entrypoint!(main);
 
 use borsh::BorshDeserialize;
 use svm_runner_lib::runner;
-use svm_runner_types::{hash_state, CommitedValues, ExecutionInput};
+use svm_runner_types::{
+    hash_forced_txs, hash_header, hash_ramp_results, hash_ramp_txs, hash_receipts, hash_state,
+    hash_txs, CommitedValues, ExecutionInput,
+};
 
 pub fn main() {
     // Read an input to the program.
@@ -13,12 +16,31 @@ pub fn main() {
 
     let input = ExecutionInput::try_from_slice(&input_bytes).unwrap();
 
-    let rollup_state = runner(input.clone());
-    let hash = hash_state(rollup_state);
+    // Only commitments to the input are made public, the batch data being
+    // published separately
+    let txs_hash = hash_txs(&input.txs);
+    let ramp_txs_hash = hash_ramp_txs(&input.ramp_txs);
+    let forced_txs_start = input.forced_txs_start;
+    let forced_txs_count = input.forced_txs.len() as u64;
+    let forced_txs_hash = hash_forced_txs(&input.forced_txs_prior_hash, &input.forced_txs);
+    let header_hash = hash_header(&input.header, &input.fees);
+    let batch_number = input.batch_number;
+
+    let runner_output = runner(input).expect("invalid batch");
+    let hash = hash_state(runner_output.state);
 
     let output = CommitedValues {
-        input,
+        prior_state_root: runner_output.prior_state_root,
+        batch_number,
         output: hash,
+        txs_hash,
+        ramp_txs_hash,
+        forced_txs_start,
+        forced_txs_count,
+        forced_txs_hash,
+        ramp_results_hash: hash_ramp_results(&runner_output.ramp_results),
+        header_hash,
+        receipts_root: hash_receipts(&runner_output.receipts),
     };
 
     let output_slice = borsh::to_vec(&output).unwrap();
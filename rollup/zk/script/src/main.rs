@@ -1,32 +1,132 @@
 use borsh::BorshDeserialize;
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
 use solana_sdk::{
-    hash::Hash, native_token::LAMPORTS_PER_SOL, signature::Keypair, signer::Signer,
-    system_instruction, system_program, transaction::Transaction,
+    hash::Hash,
+    native_token::LAMPORTS_PER_SOL,
+    signature::Keypair,
+    signer::Signer,
+    system_instruction, system_program,
+    transaction::{Transaction, VersionedTransaction},
 };
-use sp1_sdk::{include_elf, HashableKey, ProverClient, SP1Stdin};
-use std::{fs::File, vec};
+use sp1_sdk::{
+    include_elf, HashableKey, ProverClient, SP1ProofWithPublicValues, SP1Stdin, SP1VerifyingKey,
+};
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+    vec,
+};
+use svm_runner_lib::runner;
 use svm_runner_types::{
-    hash_state, CommitedValues, ExecutionInput, RampTx, RollupState, SP1Groth16Proof,
-    SerializableAccount, State,
+    hash_state, BatchData, BlockHeader, CommitedValues, ExecutionInput, FeeConfig, RampTx,
+    RollupState, RollupStatus, SP1Groth16Proof, SerializableAccount, State,
 };
 
 const ELF: &[u8] = include_elf!("zk-svm");
 
+/// Artifacts written to the output directory of `prove`
+const PROOF_FILE: &str = "proof.bin";
+const VK_HASH_FILE: &str = "vk_hash.txt";
+const COMMITED_VALUES_FILE: &str = "commited_values.json";
+/// Borsh serialized [SP1Groth16Proof], as submitted to the bridge
+const GROTH16_PROOF_FILE: &str = "grooth16_proof.bin";
+/// Borsh serialized [BatchData], the data the proof commits to
+const BATCH_FILE: &str = "batch.bin";
+/// Verifying key hash the bridge accepts proofs of, see [VK_HASH_FILE]
+const BRIDGE_VK_HASH: &str = include_str!("../vk_hash.txt");
+/// Timestamp of the test batch, fixed so that proving it again gives the same
+/// fixtures. The bridge tests set the L1 clock to it.
+const TEST_TIMESTAMP: i64 = 1_740_787_200;
+
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
 struct Args {
-    #[clap(long)]
-    execute: bool,
+    #[clap(subcommand)]
+    command: Command,
+}
 
-    #[clap(long)]
-    prove: bool,
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Executes the program without proving it
+    Execute {
+        #[clap(flatten)]
+        input: InputArgs,
+    },
+    /// Generates a proof of the execution
+    Prove {
+        #[clap(flatten)]
+        input: InputArgs,
+
+        /// Type of proof to generate
+        #[clap(long, value_enum, default_value_t = ProofType::Groth16)]
+        proof_type: ProofType,
 
+        /// Directory the proof and its artifacts are written to
+        #[clap(long, short, default_value = ".")]
+        output_dir: PathBuf,
+    },
+    /// Verifies a proof generated by `prove` against the program
+    Verify {
+        /// Proof file, or the output directory of `prove`
+        #[clap(long, default_value = PROOF_FILE)]
+        proof: PathBuf,
+
+        /// Batch data to check against the committed values of the proof
+        #[clap(long)]
+        batch: Option<PathBuf>,
+    },
+    /// Prints the verifying key hash of the program, failing if the bridge
+    /// expects another one
+    VkHash,
+}
+
+#[derive(clap::Args, Debug)]
+struct InputArgs {
+    /// File holding the `ExecutionInput`, a test input is used if not provided
     #[clap(long)]
-    input: Option<Vec<u8>>,
+    input: Option<PathBuf>,
+
+    /// Encoding of the input file, guessed from its extension if not provided
+    #[clap(long, value_enum)]
+    input_format: Option<InputFormat>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum InputFormat {
+    Borsh,
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ProofType {
+    Core,
+    Compressed,
+    Groth16,
+    Plonk,
+}
+
+impl InputArgs {
+    fn load(&self) -> ExecutionInput {
+        let Some(path) = &self.input else {
+            return create_test_input();
+        };
 
-    #[clap(long, short, default_value = "./proof.bin")]
-    output_path: String,
+        let format = self.input_format.unwrap_or_else(|| {
+            match path.extension().and_then(|extension| extension.to_str()) {
+                Some("json") => InputFormat::Json,
+                _ => InputFormat::Borsh,
+            }
+        });
+        let bytes = fs::read(path).expect("failed to read input file");
+        match format {
+            InputFormat::Borsh => {
+                ExecutionInput::try_from_slice(&bytes).expect("failed to parse borsh input")
+            }
+            InputFormat::Json => {
+                serde_json::from_slice(&bytes).expect("failed to parse JSON input")
+            }
+        }
+    }
 }
 
 fn create_test_input() -> ExecutionInput {
@@ -39,121 +139,220 @@ fn create_test_input() -> ExecutionInput {
     let kp_receiver = Keypair::from_bytes(&kp_receiver_bytes).unwrap();
     let pk_receiver = kp_receiver.pubkey();
 
-    let transactions = vec![Transaction::new_signed_with_payer(
-        &[system_instruction::transfer(
-            &kp_sender.try_pubkey().unwrap(),
-            &pk_receiver,
-            LAMPORTS_PER_SOL,
-        )],
-        Some(&kp_sender.try_pubkey().unwrap()),
-        &[&kp_sender],
-        Hash::new_from_array([7; 32]),
-    )];
+    let accounts = RollupState {
+        states: vec![
+            State {
+                pubkey: kp_sender.try_pubkey().unwrap(),
+                account: SerializableAccount {
+                    lamports: 10 * LAMPORTS_PER_SOL,
+                    data: vec![],
+                    owner: system_program::id(),
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            },
+            State {
+                pubkey: pk_receiver,
+                account: SerializableAccount {
+                    lamports: 0,
+                    data: vec![],
+                    owner: system_program::id(),
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            },
+        ],
+    };
+    // The state has no status yet, so the only valid blockhash is the genesis one
+    let recent_blockhash = RollupStatus::genesis(&hash_state(accounts.clone())).latest_blockhash();
 
+    let transfer = |lamports| {
+        Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(
+                &kp_sender.try_pubkey().unwrap(),
+                &pk_receiver,
+                lamports,
+            )],
+            Some(&kp_sender.try_pubkey().unwrap()),
+            &[&kp_sender],
+            recent_blockhash,
+        )
+    };
+    let transactions = vec![transfer(LAMPORTS_PER_SOL)];
     let serialized_transactions = bincode::serialize(&transactions).unwrap();
 
+    // The bridge tests queue the same ramp txs and force the same transaction,
+    // so that proving covers both queues and more than one ramp tx
+    let forced_tx = VersionedTransaction::from(transfer(LAMPORTS_PER_SOL / 2));
+    let forced_txs = vec![bincode::serialize(&forced_tx).unwrap()];
+
     ExecutionInput {
-        accounts: RollupState {
-            states: vec![
-                State {
-                    pubkey: kp_sender.try_pubkey().unwrap(),
-                    account: SerializableAccount {
-                        lamports: 10 * LAMPORTS_PER_SOL,
-                        data: vec![],
-                        owner: system_program::id(),
-                        executable: false,
-                        rent_epoch: 0,
-                    },
-                },
-                State {
-                    pubkey: pk_receiver,
-                    account: SerializableAccount {
-                        lamports: 0,
-                        data: vec![],
-                        owner: system_program::id(),
-                        executable: false,
-                        rent_epoch: 0,
-                    },
-                },
-            ],
-        },
+        accounts,
         txs: serialized_transactions,
-        ramp_txs: vec![RampTx {
-            is_onramp: true,
-            user: kp_sender.try_pubkey().unwrap(),
-            amount: LAMPORTS_PER_SOL,
-        }],
+        ramp_txs: vec![
+            RampTx {
+                is_onramp: true,
+                user: kp_sender.try_pubkey().unwrap(),
+                amount: LAMPORTS_PER_SOL,
+                token: None,
+            },
+            RampTx {
+                is_onramp: true,
+                user: pk_receiver,
+                amount: LAMPORTS_PER_SOL,
+                token: None,
+            },
+        ],
+        forced_txs,
+        forced_txs_start: 0,
+        forced_txs_prior_hash: Hash::default(),
+        header: BlockHeader {
+            slot: 1,
+            unix_timestamp: TEST_TIMESTAMP,
+            ..BlockHeader::default()
+        },
+        fees: FeeConfig::default(),
+        batch_number: 1,
     }
 }
 
-fn main() {
-    let args = Args::parse();
-
-    if args.execute == args.prove {
-        eprintln!("Error: You must specify either --execute or --prove");
-        std::process::exit(1);
-    }
+fn stdin_for(input: &ExecutionInput) -> SP1Stdin {
+    let mut stdin = SP1Stdin::new();
+    stdin.write_slice(&borsh::to_vec(input).unwrap());
+    stdin
+}
 
-    // Default to test input if user does not provide
-    let input = if let Some(input) = args.input {
-        bincode::deserialize(&input).unwrap()
-    } else {
-        create_test_input()
-    };
+fn execute(input: ExecutionInput) {
+    let client = ProverClient::from_env();
 
-    let bytes = borsh::to_vec(&input).unwrap();
+    // Execute the program
+    let (output, report) = client.execute(ELF, &stdin_for(&input)).run().unwrap();
+    println!("Program executed successfully.");
 
-    let client = ProverClient::from_env();
-    let mut stdin = SP1Stdin::new();
+    let commited_values = CommitedValues::try_from_slice(&output.to_vec()).unwrap();
+    println!("committed values: {:#?}", &commited_values);
 
-    stdin.write_slice(&bytes);
+    // Record the number of cycles executed.
+    println!("Number of cycles: {}", report.total_instruction_count());
+}
 
-    if args.execute {
-        // Execute the program
-        let (output, report) = client.execute(ELF, &stdin).run().unwrap();
-        println!("Program executed successfully.");
+fn prove(input: ExecutionInput, proof_type: ProofType, output_dir: &Path) {
+    let client = ProverClient::from_env();
+    let stdin = stdin_for(&input);
+    println!("Initial state hash: {}", hash_state(input.accounts.clone()));
 
-        let commited_values = CommitedValues::try_from_slice(&output.to_vec()).unwrap();
-        println!("committed values: {:#?}", &commited_values);
+    // Setup the program for proving.
+    let (pk, vk) = client.setup(ELF);
+    println!("Verifying key hash: {}", vk.bytes32());
 
-        // Record the number of cycles executed.
-        println!("Number of cycles: {}", report.total_instruction_count());
-    } else {
-        println!("Initial state hash: {}", hash_state(input.accounts));
+    println!("Starting {proof_type:?} proof generation...");
+    let request = client.prove(&pk, &stdin);
+    let request = match proof_type {
+        ProofType::Core => request.core(),
+        ProofType::Compressed => request.compressed(),
+        ProofType::Groth16 => request.groth16(),
+        ProofType::Plonk => request.plonk(),
+    };
+    let proof = request.run().expect("failed to generate proof");
+    println!("Successfully generated proof!");
 
-        // Setup the program for proving.
-        let (pk, vk) = client.setup(ELF);
-        println!("Verifying key: {}", vk.bytes32());
+    // Verify the proof before writing anything.
+    client.verify(&proof, &vk).expect("failed to verify proof");
+    println!("Successfully verified proof!");
 
-        println!("Starting proof generation...");
-        let proof = client
-            .prove(&pk, &stdin)
-            .groth16()
-            .run()
-            .expect("failed to generate proof");
-        proof.save(args.output_path).expect("failed to save proof");
+    let output = CommitedValues::try_from_slice(&proof.public_values.to_vec()).unwrap();
+    println!("Final state hash: {:?}", output.output);
 
-        let output = CommitedValues::try_from_slice(&proof.public_values.to_vec()).unwrap();
-        println!("Final state hash: {:?}", output.output);
+    // The ramp results are not public, they are published with the batch
+    let batch = BatchData {
+        ramp_results: runner(input.clone()).expect("invalid batch").ramp_results,
+        input,
+    };
+    assert!(
+        batch.matches(&output),
+        "batch data does not match the committed values"
+    );
 
-        println!("Successfully generated proof!");
+    fs::create_dir_all(output_dir).expect("failed to create output directory");
+    fs::write(output_dir.join(BATCH_FILE), borsh::to_vec(&batch).unwrap())
+        .expect("failed to save batch data");
+    proof
+        .save(output_dir.join(PROOF_FILE))
+        .expect("failed to save proof");
+    fs::write(output_dir.join(VK_HASH_FILE), vk.bytes32()).expect("failed to save vk hash");
+    let commited_values_file =
+        File::create(output_dir.join(COMMITED_VALUES_FILE)).expect("failed to open file");
+    serde_json::to_writer_pretty(commited_values_file, &output)
+        .expect("failed to save committed values");
 
+    if let ProofType::Groth16 = proof_type {
+        println!("Writing borsh serializable grooth16 proof to file...");
         let grooth16_proof = SP1Groth16Proof {
             proof: proof.bytes(),
             sp1_public_inputs: output,
         };
+        let mut proof_borsh_file =
+            File::create(output_dir.join(GROTH16_PROOF_FILE)).expect("failed to open file");
+        borsh::to_writer(&mut proof_borsh_file, &grooth16_proof)
+            .expect("borsh unable to write to file");
+    }
 
-        println!("Writing borsh serializable grooth16 proof to file...");
+    println!("Proof artifacts written to {}", output_dir.display());
+}
 
-        let mut proof_borsh_file = File::create("grooth16_proof.bin").expect("failed to open file");
+fn verify(path: &Path, batch: Option<&Path>) {
+    let path = if path.is_dir() {
+        path.join(PROOF_FILE)
+    } else {
+        path.to_path_buf()
+    };
 
-        borsh::to_writer(&mut proof_borsh_file, &grooth16_proof)
-            .expect("borsh unable to write to file");
+    // The verifying key is derived from the program, no prover network is needed
+    let client = ProverClient::builder().cpu().build();
+    let (_, vk): (_, SP1VerifyingKey) = client.setup(ELF);
+    println!("Verifying key hash: {}", vk.bytes32());
+
+    let proof = SP1ProofWithPublicValues::load(&path).expect("failed to load proof");
+    client.verify(&proof, &vk).expect("failed to verify proof");
+    println!("Successfully verified proof!");
+
+    let output = CommitedValues::try_from_slice(&proof.public_values.to_vec()).unwrap();
+    println!("Final state hash: {:?}", output.output);
+
+    if let Some(batch) = batch {
+        let batch = BatchData::try_from_slice(&fs::read(batch).expect("failed to read batch"))
+            .expect("failed to parse batch");
+        assert!(
+            batch.matches(&output),
+            "batch data does not match the committed values"
+        );
+        println!("Batch data matches the committed values!");
+    }
+}
+
+fn vk_hash() {
+    let client = ProverClient::builder().cpu().build();
+    let (_, vk): (_, SP1VerifyingKey) = client.setup(ELF);
+    println!("Verifying key hash: {}", vk.bytes32());
+
+    if vk.bytes32() != BRIDGE_VK_HASH {
+        eprintln!("The bridge expects {BRIDGE_VK_HASH}, run `prove` to update it");
+        std::process::exit(1);
+    }
+    println!("The bridge expects this verifying key hash!");
+}
 
-        println!("Successfully written to file!");
+fn main() {
+    let args = Args::parse();
 
-        // Verify the proof.
-        client.verify(&proof, &vk).expect("failed to verify proof");
-        println!("Successfully verified proof!");
+    match args.command {
+        Command::Execute { input } => execute(input.load()),
+        Command::Prove {
+            input,
+            proof_type,
+            output_dir,
+        } => prove(input.load(), proof_type, &output_dir),
+        Command::Verify { proof, batch } => verify(&proof, batch.as_deref()),
+        Command::VkHash => vk_hash(),
     }
 }